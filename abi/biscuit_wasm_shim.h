@@ -0,0 +1,361 @@
+/* Generated from the `wasm_export!` manifest of biscuit-wasm-shim, do not edit */
+
+#ifndef BISCUIT_WASM_SHIM_H
+#define BISCUIT_WASM_SHIM_H
+
+#include <stdint.h>
+
+/* pointers and lengths are offsets and sizes in the linear memory of the module */
+
+/* ErrPanic is returned by any export when the module is built with catch-panic */
+typedef enum {
+    ResultKind_Ok = 0,
+    ResultKind_ErrBiscuit = 1,
+    ResultKind_ErrSerialization = 2,
+    ResultKind_OkNone = 3,
+    ResultKind_ErrPanic = 4,
+} ResultKind;
+
+/* return area, passed first to every export */
+typedef struct {
+    uint32_t ptr;
+    uint32_t len;
+    uint32_t kind;
+} WasmResult;
+
+_Static_assert(sizeof(WasmResult) == 12, "WasmResult is 12 bytes on wasm32");
+
+typedef enum {
+    SignatureAlgorithm_Ed25519 = 0,
+    SignatureAlgorithm_Secp256r1 = 1,
+} SignatureAlgorithm;
+
+typedef enum {
+    DatalogKind_Block = 0,
+    DatalogKind_Authorizer = 1,
+    DatalogKind_Fact = 2,
+    DatalogKind_Rule = 3,
+    DatalogKind_Check = 4,
+    DatalogKind_Policy = 5,
+} DatalogKind;
+
+#define BISCUIT_WASM_SHIM_ABI_VERSION 1
+
+/* values allocated by the shim, referenced by their offset */
+typedef uint32_t Authorizer;
+typedef uint32_t AuthorizerBuilder;
+typedef uint32_t Biscuit;
+typedef uint32_t BiscuitBuilder;
+typedef uint32_t BlockBuilder;
+typedef uint32_t KeyPair;
+typedef uint32_t PrivateKey;
+typedef uint32_t PublicKey;
+typedef uint32_t RunLimits;
+typedef uint32_t ThirdPartyBlock;
+typedef uint32_t ThirdPartyRequest;
+typedef uint32_t UnsignedBiscuit;
+
+/* exported as malloc and free by the wasm32-unknown-unknown module,
+ * as shim_malloc and shim_free by the wasm32-wasip1 module */
+uint32_t shim_malloc(uint32_t size, uint32_t align);
+void shim_free(uint32_t ptr, uint32_t size, uint32_t align);
+uint32_t get_return_area(void);
+void wasm_result_free(uint32_t result);
+void secret_string_free(uint32_t ptr, uint32_t len);
+
+/* returns an u32 in ptr */
+void abi_version(uint32_t ret);
+
+/* returns bytes, released with wasm_result_free, or an error */
+void authorize_batch(uint32_t ret, AuthorizerBuilder builder, RunLimits run_limits, uint32_t tokens_ptr, uint32_t tokens_len, PublicKey root_public_key);
+
+/* returns an u32 in ptr, or an error
+ * run_limits (RunLimits) is consumed */
+void authorizer_authorize(uint32_t ret, Authorizer authorizer, RunLimits run_limits);
+
+/* returns nothing, or an error */
+void authorizer_builder_add_code(uint32_t ret, AuthorizerBuilder builder, uint32_t code_ptr, uint32_t code_len);
+
+/* returns the offset of a Authorizer in ptr, owned by the caller, or an error
+ * builder (AuthorizerBuilder) is consumed */
+void authorizer_builder_build(uint32_t ret, AuthorizerBuilder builder, Biscuit token);
+
+/* returns nothing
+ * builder (AuthorizerBuilder) is consumed */
+void authorizer_builder_drop(uint32_t ret, AuthorizerBuilder builder);
+
+/* returns the offset of a AuthorizerBuilder in ptr, owned by the caller, or an error */
+void authorizer_builder_from_json(uint32_t ret, uint32_t json_ptr, uint32_t json_len);
+
+/* returns a string, released with wasm_result_free, or an error */
+void authorizer_builder_lint(uint32_t ret, AuthorizerBuilder builder);
+
+/* returns nothing, or an error
+ * other (AuthorizerBuilder) is consumed */
+void authorizer_builder_merge(uint32_t ret, AuthorizerBuilder builder, AuthorizerBuilder other);
+
+/* returns the offset of a AuthorizerBuilder in ptr, owned by the caller */
+void authorizer_builder_new(uint32_t ret);
+
+/* returns nothing */
+void authorizer_builder_register_extern_func(uint32_t ret, AuthorizerBuilder builder, uint32_t name_ptr, uint32_t name_len);
+
+/* returns a string, released with wasm_result_free, or an error */
+void authorizer_builder_to_json(uint32_t ret, AuthorizerBuilder builder);
+
+/* returns a string, released with wasm_result_free */
+void authorizer_builder_to_string(uint32_t ret, AuthorizerBuilder builder);
+
+/* returns nothing
+ * authorizer (Authorizer) is consumed */
+void authorizer_drop(uint32_t ret, Authorizer authorizer);
+
+/* returns a string, released with wasm_result_free, or an error */
+void authorizer_dump_json(uint32_t ret, Authorizer authorizer);
+
+/* returns a string, released with wasm_result_free */
+void authorizer_print_world(uint32_t ret, Authorizer authorizer);
+
+/* returns the offset of a RunLimits in ptr, owned by the caller */
+void authorizer_run_limits(uint32_t ret, uint64_t max_time);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error
+ * builder (BlockBuilder) is consumed */
+void biscuit_append(uint32_t ret, Biscuit biscuit, BlockBuilder builder);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error
+ * block (ThirdPartyBlock) is consumed */
+void biscuit_append_third_party(uint32_t ret, Biscuit biscuit, PublicKey external_key, ThirdPartyBlock block);
+
+/* returns a string, released with wasm_result_free */
+void biscuit_auth_version(uint32_t ret);
+
+/* returns the offset of a Authorizer in ptr, owned by the caller, or an error */
+void biscuit_authorizer(uint32_t ret, Biscuit biscuit);
+
+/* returns a string, released with wasm_result_free, or OkNone, or an error */
+void biscuit_block_context(uint32_t ret, Biscuit biscuit, uint32_t index);
+
+/* returns the offset of a BiscuitBuilder in ptr, owned by the caller */
+void biscuit_builder(uint32_t ret);
+
+/* returns nothing, or an error */
+void biscuit_builder_add_check(uint32_t ret, BiscuitBuilder builder, uint32_t check_ptr, uint32_t check_len);
+
+/* returns nothing, or an error */
+void biscuit_builder_add_code(uint32_t ret, BiscuitBuilder builder, uint32_t code_ptr, uint32_t code_len);
+
+/* returns nothing, or an error */
+void biscuit_builder_add_fact(uint32_t ret, BiscuitBuilder builder, uint32_t fact_ptr, uint32_t fact_len);
+
+/* returns nothing, or an error */
+void biscuit_builder_add_rule(uint32_t ret, BiscuitBuilder builder, uint32_t rule_ptr, uint32_t rule_len);
+
+/* returns nothing */
+void biscuit_builder_add_scope_authority(uint32_t ret, BiscuitBuilder builder);
+
+/* returns nothing */
+void biscuit_builder_add_scope_previous(uint32_t ret, BiscuitBuilder builder);
+
+/* returns nothing */
+void biscuit_builder_add_scope_public_key(uint32_t ret, BiscuitBuilder builder, PublicKey public_key);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error
+ * builder (BiscuitBuilder) is consumed */
+void biscuit_builder_build_with_key_pair(uint32_t ret, BiscuitBuilder builder, KeyPair root_keypair);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error
+ * builder (BiscuitBuilder) is consumed */
+void biscuit_builder_build_with_private_key(uint32_t ret, BiscuitBuilder builder, PrivateKey private_root_key);
+
+/* returns nothing, or an error */
+void biscuit_builder_check_expiration_date(uint32_t ret, BiscuitBuilder builder, uint64_t unix_seconds);
+
+/* returns nothing */
+void biscuit_builder_check_operation(uint32_t ret, BiscuitBuilder builder, uint32_t operation_ptr, uint32_t operation_len);
+
+/* returns nothing */
+void biscuit_builder_check_resource(uint32_t ret, BiscuitBuilder builder, uint32_t name_ptr, uint32_t name_len);
+
+/* returns nothing */
+void biscuit_builder_check_resource_prefix(uint32_t ret, BiscuitBuilder builder, uint32_t prefix_ptr, uint32_t prefix_len);
+
+/* returns nothing */
+void biscuit_builder_check_resource_suffix(uint32_t ret, BiscuitBuilder builder, uint32_t suffix_ptr, uint32_t suffix_len);
+
+/* returns nothing
+ * builder (BiscuitBuilder) is consumed */
+void biscuit_builder_drop(uint32_t ret, BiscuitBuilder builder);
+
+/* returns the offset of a BiscuitBuilder in ptr, owned by the caller, or an error */
+void biscuit_builder_from_json(uint32_t ret, uint32_t json_ptr, uint32_t json_len);
+
+/* returns nothing, or an error
+ * other (BiscuitBuilder) is consumed */
+void biscuit_builder_merge(uint32_t ret, BiscuitBuilder builder, BiscuitBuilder other);
+
+/* returns the offset of a BiscuitBuilder in ptr, owned by the caller */
+void biscuit_builder_new(uint32_t ret);
+
+/* returns the offset of a UnsignedBiscuit in ptr, owned by the caller, or an error
+ * builder (BiscuitBuilder) is consumed */
+void biscuit_builder_prepare_external_signature(uint32_t ret, BiscuitBuilder builder, PublicKey root_public_key);
+
+/* returns nothing */
+void biscuit_builder_set_context(uint32_t ret, BiscuitBuilder builder, uint32_t context_ptr, uint32_t context_len);
+
+/* returns nothing, or an error */
+void biscuit_builder_set_root_key_id(uint32_t ret, BiscuitBuilder builder, uint32_t root_key_id);
+
+/* returns a string, released with wasm_result_free, or an error */
+void biscuit_builder_to_json(uint32_t ret, BiscuitBuilder builder);
+
+/* returns a string, released with wasm_result_free */
+void biscuit_builder_to_string(uint32_t ret, BiscuitBuilder builder);
+
+/* returns nothing
+ * biscuit (Biscuit) is consumed */
+void biscuit_drop(uint32_t ret, Biscuit biscuit);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error */
+void biscuit_from_base64(uint32_t ret, uint32_t data_ptr, uint32_t data_len, PublicKey root_public_key);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error */
+void biscuit_from_bytes(uint32_t ret, uint32_t data_ptr, uint32_t data_len, PublicKey root_public_key);
+
+/* returns an u32 in ptr, or OkNone */
+void biscuit_root_key_id(uint32_t ret, Biscuit biscuit);
+
+/* returns the offset of a ThirdPartyRequest in ptr, owned by the caller, or an error */
+void biscuit_third_party_request(uint32_t ret, Biscuit biscuit);
+
+/* returns a string, released with wasm_result_free, or an error */
+void biscuit_to_base64(uint32_t ret, Biscuit biscuit);
+
+/* returns bytes, released with wasm_result_free, or an error */
+void biscuit_to_bytes(uint32_t ret, Biscuit biscuit);
+
+/* returns an u32 in ptr, or an error */
+void biscuit_verify_and_authorize(uint32_t ret, uint32_t token_ptr, uint32_t token_len, uint32_t public_key_ptr, uint32_t public_key_len, uint32_t authorizer_code_ptr, uint32_t authorizer_code_len, uint32_t params_json_ptr, uint32_t params_json_len, uint32_t limits_json_ptr, uint32_t limits_json_len);
+
+/* returns nothing, or an error */
+void block_builder_add_code(uint32_t ret, BlockBuilder builder, uint32_t code_ptr, uint32_t code_len);
+
+/* returns nothing, or an error */
+void block_builder_check_expiration_date(uint32_t ret, BlockBuilder builder, uint64_t unix_seconds);
+
+/* returns nothing */
+void block_builder_check_operation(uint32_t ret, BlockBuilder builder, uint32_t operation_ptr, uint32_t operation_len);
+
+/* returns nothing */
+void block_builder_check_resource(uint32_t ret, BlockBuilder builder, uint32_t name_ptr, uint32_t name_len);
+
+/* returns nothing */
+void block_builder_check_resource_prefix(uint32_t ret, BlockBuilder builder, uint32_t prefix_ptr, uint32_t prefix_len);
+
+/* returns nothing */
+void block_builder_check_resource_suffix(uint32_t ret, BlockBuilder builder, uint32_t suffix_ptr, uint32_t suffix_len);
+
+/* returns nothing
+ * builder (BlockBuilder) is consumed */
+void block_builder_drop(uint32_t ret, BlockBuilder builder);
+
+/* returns the offset of a BlockBuilder in ptr, owned by the caller */
+void block_builder_new(uint32_t ret);
+
+/* returns nothing */
+void block_builder_set_context(uint32_t ret, BlockBuilder builder, uint32_t context_ptr, uint32_t context_len);
+
+/* returns a string, released with wasm_result_free */
+void block_builder_to_string(uint32_t ret, BlockBuilder builder);
+
+/* returns a string, released with wasm_result_free, or an error */
+void datalog_format(uint32_t ret, uint32_t code_ptr, uint32_t code_len);
+
+/* returns nothing, or an error */
+void datalog_validate(uint32_t ret, uint32_t code_ptr, uint32_t code_len, DatalogKind kind);
+
+/* returns a secret string, released with secret_string_free */
+void dispatch(uint32_t ret, uint32_t request_ptr, uint32_t request_len);
+
+/* returns nothing
+ * keypair (KeyPair) is consumed */
+void keypair_drop(uint32_t ret, KeyPair keypair);
+
+/* returns the offset of a KeyPair in ptr, owned by the caller, or an error */
+void keypair_from_passphrase(uint32_t ret, SignatureAlgorithm signature_algorithm, uint32_t passphrase_ptr, uint32_t passphrase_len, uint32_t salt_ptr, uint32_t salt_len);
+
+/* returns the offset of a KeyPair in ptr, owned by the caller */
+void keypair_from_private_key(uint32_t ret, PrivateKey private_key);
+
+/* returns the offset of a KeyPair in ptr, owned by the caller, or an error */
+void keypair_from_seed(uint32_t ret, SignatureAlgorithm signature_algorithm, uint32_t seed_ptr, uint32_t seed_len);
+
+/* returns the offset of a KeyPair in ptr, owned by the caller */
+void keypair_new(uint32_t ret, SignatureAlgorithm signature_algorithm);
+
+/* returns the offset of a PrivateKey in ptr, owned by the caller */
+void keypair_private_key(uint32_t ret, KeyPair keypair);
+
+/* returns the offset of a PublicKey in ptr, owned by the caller */
+void keypair_public_key(uint32_t ret, KeyPair keypair);
+
+/* returns bytes, released with wasm_result_free, or an error */
+void keypair_sign(uint32_t ret, KeyPair keypair, uint32_t data_ptr, uint32_t data_len);
+
+/* returns nothing
+ * private_key (PrivateKey) is consumed */
+void private_key_drop(uint32_t ret, PrivateKey private_key);
+
+/* returns the offset of a PrivateKey in ptr, owned by the caller, or an error */
+void private_key_from_hex(uint32_t ret, uint32_t data_ptr, uint32_t data_len);
+
+/* returns a secret string, released with secret_string_free */
+void private_key_to_hex(uint32_t ret, PrivateKey private_key);
+
+/* returns the offset of a PublicKey in ptr, owned by the caller, or an error */
+void public_key_from_hex(uint32_t ret, uint32_t data_ptr, uint32_t data_len, SignatureAlgorithm algorithm);
+
+/* returns a string, released with wasm_result_free */
+void public_key_to_hex(uint32_t ret, PublicKey public_key);
+
+/* returns a string, released with wasm_result_free */
+void shim_features(uint32_t ret);
+
+/* returns a string, released with wasm_result_free */
+void shim_version(uint32_t ret);
+
+/* returns nothing
+ * block (ThirdPartyBlock) is consumed */
+void third_party_block_drop(uint32_t ret, ThirdPartyBlock block);
+
+/* returns a string, released with wasm_result_free, or an error */
+void third_party_block_to_base64(uint32_t ret, ThirdPartyBlock block);
+
+/* returns the offset of a ThirdPartyBlock in ptr, owned by the caller, or an error
+ * request (ThirdPartyRequest) is consumed
+ * builder (BlockBuilder) is consumed */
+void third_party_request_create_block(uint32_t ret, ThirdPartyRequest request, PrivateKey private_key, BlockBuilder builder);
+
+/* returns nothing
+ * request (ThirdPartyRequest) is consumed */
+void third_party_request_drop(uint32_t ret, ThirdPartyRequest request);
+
+/* returns the offset of a ThirdPartyRequest in ptr, owned by the caller, or an error */
+void third_party_request_from_base64(uint32_t ret, uint32_t data_ptr, uint32_t data_len);
+
+/* returns a string, released with wasm_result_free, or an error */
+void third_party_request_to_base64(uint32_t ret, ThirdPartyRequest request);
+
+/* returns nothing
+ * unsigned_biscuit (UnsignedBiscuit) is consumed */
+void unsigned_biscuit_drop(uint32_t ret, UnsignedBiscuit unsigned_biscuit);
+
+/* returns the offset of a Biscuit in ptr, owned by the caller, or an error
+ * unsigned_biscuit (UnsignedBiscuit) is consumed */
+void unsigned_biscuit_finish(uint32_t ret, UnsignedBiscuit unsigned_biscuit, uint32_t signature_ptr, uint32_t signature_len);
+
+/* returns bytes, released with wasm_result_free */
+void unsigned_biscuit_signature_payload(uint32_t ret, UnsignedBiscuit unsigned_biscuit);
+
+#endif
@@ -0,0 +1,13 @@
+//! Writes the manifest of the exports as JSON and as a C header
+//!
+//! cargo run --example manifest --features manifest,extern-funcs,dispatch -- abi
+
+use biscuit_wasm_shim::manifest;
+use std::path::PathBuf;
+
+fn main() {
+    let dir = PathBuf::from(std::env::args().nth(1).unwrap_or_else(|| "abi".to_string()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("biscuit_wasm_shim.json"), manifest::to_json()).unwrap();
+    std::fs::write(dir.join("biscuit_wasm_shim.h"), manifest::to_c_header()).unwrap();
+}
@@ -0,0 +1,669 @@
+//! Host side harness: builds the shim for `wasm32-unknown-unknown` or `wasm32-wasip1`,
+//! loads it in the wasmi interpreter and calls its exports through the wasm32 ABI, the way
+//! host bindings do (`&str`/`&[u8]` as pointer and length pairs, opaque pointers
+//! as `i32`, results read from a return area)
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+use wasmi::core::{F64, ValType};
+use wasmi::{
+    Caller, Engine, Extern, ExternType, Func, FuncType, Instance, Linker, Memory, Module, Store,
+    Val,
+};
+
+/// Features of the module under test, every optional import and export is enabled
+pub const FEATURES: &str = "print,extern-funcs,dispatch";
+
+/// Exports of the module that are not operations of the shim
+const INTERNAL_EXPORTS: &[&str] = &[
+    "memory",
+    "malloc",
+    "free",
+    "shim_malloc",
+    "shim_free",
+    "get_return_area",
+];
+
+/// Target the module is built for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// imports `__getrandom_custom` and the wasm-bindgen clock
+    Unknown,
+    /// imports WASI preview 1 functions, allocates with `shim_malloc` and `shim_free`
+    Wasip1,
+}
+
+impl Target {
+    fn triple(self) -> &'static str {
+        match self {
+            Target::Unknown => "wasm32-unknown-unknown",
+            Target::Wasip1 => "wasm32-wasip1",
+        }
+    }
+
+    fn malloc(self) -> &'static str {
+        match self {
+            Target::Unknown => "malloc",
+            Target::Wasip1 => "shim_malloc",
+        }
+    }
+
+    fn free(self) -> &'static str {
+        match self {
+            Target::Unknown => "free",
+            Target::Wasip1 => "shim_free",
+        }
+    }
+}
+
+/// Builds the release module once per test binary and target
+///
+/// The module is built in its own target directory, the one of the workspace
+/// is locked by the `cargo test` invocation running this harness.
+pub fn module_path(target: Target) -> &'static Path {
+    static UNKNOWN: OnceLock<PathBuf> = OnceLock::new();
+    static WASIP1: OnceLock<PathBuf> = OnceLock::new();
+    let module = match target {
+        Target::Unknown => &UNKNOWN,
+        Target::Wasip1 => &WASIP1,
+    };
+    module.get_or_init(|| build(target.triple(), FEATURES))
+}
+
+/// Builds the release component exposing `wit/shim.wit` once per test binary
+pub fn component_path() -> &'static Path {
+    static COMPONENT: OnceLock<PathBuf> = OnceLock::new();
+    COMPONENT.get_or_init(|| build("wasm32-wasip2", "component"))
+}
+
+fn build(triple: &str, features: &str) -> PathBuf {
+    let workspace = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+    let target_dir = workspace.join("target").join("host-tests");
+    let status = Command::new(option_env!("CARGO").unwrap_or("cargo"))
+        .current_dir(workspace)
+        .args(["build", "--release", "--target", triple])
+        .args(["--package", "biscuit-wasm-shim", "--features", features])
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .status()
+        .expect("cargo is available");
+    assert!(status.success(), "the wasm module does not build");
+    target_dir.join(format!("{triple}/release/biscuit_wasm_shim.wasm"))
+}
+
+/// State of the host, shared with the imports
+#[derive(Default)]
+pub struct Host {
+    /// messages written through the `print` import
+    pub printed: Vec<String>,
+    /// bytes written to stdout and stderr through the WASI `fd_write` import
+    pub output: Vec<u8>,
+    /// exports called so far
+    pub called: BTreeSet<String>,
+    rng: u64,
+    start: Option<Instant>,
+}
+
+/// Kind of a return area, as `ResultKind`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Ok,
+    ErrBiscuit,
+    ErrSerialization,
+    OkNone,
+    ErrPanic,
+}
+
+impl Kind {
+    fn from_raw(kind: u32) -> Self {
+        match kind {
+            0 => Kind::Ok,
+            1 => Kind::ErrBiscuit,
+            2 => Kind::ErrSerialization,
+            3 => Kind::OkNone,
+            4 => Kind::ErrPanic,
+            kind => panic!("unknown result kind {kind}"),
+        }
+    }
+}
+
+/// Argument of an export, after the return area
+#[derive(Debug, Clone, Copy)]
+pub enum Arg<'a> {
+    Str(&'a str),
+    Bytes(&'a [u8]),
+    U32(u32),
+    U64(u64),
+    /// opaque pointer returned by a previous call, or a `#[repr(C)]` enum
+    Ptr(u32),
+}
+
+/// Content of the return area after a successful call
+#[derive(Debug)]
+pub struct Output {
+    pub kind: Kind,
+    /// `data` of the return area: an opaque pointer, a number or a buffer address
+    pub data: u32,
+    /// copy of the buffer, released right after the call
+    pub bytes: Vec<u8>,
+}
+
+impl Output {
+    pub fn handle(&self) -> u32 {
+        assert_eq!(self.kind, Kind::Ok);
+        assert_eq!(self.bytes.len(), 0, "opaque values have no length");
+        assert_ne!(self.data, 0, "opaque values are not null");
+        self.data
+    }
+
+    pub fn string(&self) -> String {
+        assert_eq!(self.kind, Kind::Ok);
+        String::from_utf8(self.bytes.clone()).unwrap()
+    }
+
+    pub fn number(&self) -> u32 {
+        assert_eq!(self.kind, Kind::Ok);
+        self.data
+    }
+
+    pub fn option(&self) -> Option<u32> {
+        match self.kind {
+            Kind::OkNone => None,
+            _ => Some(self.number()),
+        }
+    }
+
+    pub fn unit(&self) {
+        assert_eq!(self.kind, Kind::Ok);
+        assert_eq!((self.data, self.bytes.len()), (0, 0));
+    }
+}
+
+/// Error returned in the return area
+#[derive(Debug)]
+pub struct Failure {
+    pub kind: Kind,
+    pub message: String,
+}
+
+/// Instance of the module with its host
+pub struct Shim {
+    store: Store<Host>,
+    instance: Instance,
+    memory: Memory,
+    exports: Vec<String>,
+    target: Target,
+    /// return area allocated once with `get_return_area`
+    ret: i32,
+}
+
+fn read_u32(memory: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(memory[offset..offset + 4].try_into().unwrap())
+}
+
+fn utf8(memory: &[u8], ptr: i32, len: i32) -> String {
+    String::from_utf8_lossy(&memory[ptr as usize..(ptr + len) as usize]).into_owned()
+}
+
+fn zero(ty: &ValType) -> Val {
+    match ty {
+        ValType::I32 => Val::I32(0),
+        ValType::I64 => Val::I64(0),
+        ValType::F32 => Val::F32(0f32.into()),
+        ValType::F64 => Val::F64(0f64.into()),
+        ty => panic!("unexpected type {ty:?}"),
+    }
+}
+
+fn caller_memory(caller: &Caller<'_, Host>) -> Memory {
+    match caller.get_export("memory") {
+        Some(Extern::Memory(memory)) => memory,
+        _ => panic!("the module exports its memory"),
+    }
+}
+
+/// `extern_func_call`: the host implements `add` on integers, other names are errors
+fn extern_func_call(mut caller: Caller<'_, Host>, params: &[Val]) -> Result<(), wasmi::Error> {
+    let [name_ptr, name_len, args_ptr, args_len, result] = params
+        .iter()
+        .map(|param| param.i32().unwrap())
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap();
+    let memory = caller_memory(&caller);
+    let (name, args) = {
+        let data = memory.data(&caller);
+        (
+            utf8(data, name_ptr, name_len),
+            utf8(data, args_ptr, args_len),
+        )
+    };
+    let (reply, kind) = match name.as_str() {
+        "add" => {
+            let args: Vec<serde_json::Value> = serde_json::from_str(&args).unwrap();
+            let sum: i64 = args.iter().map(|arg| arg.as_i64().unwrap()).sum();
+            (sum.to_string(), 0)
+        }
+        name => (format!("unknown extern function {name}"), 1),
+    };
+    let Some(Extern::Func(malloc)) = caller
+        .get_export("malloc")
+        .or_else(|| caller.get_export("shim_malloc"))
+    else {
+        panic!("the module exports malloc");
+    };
+    let mut ptr = [Val::I32(0)];
+    malloc.call(
+        &mut caller,
+        &[Val::I32(reply.len() as i32), Val::I32(1)],
+        &mut ptr,
+    )?;
+    let ptr = ptr[0].i32().unwrap();
+    let mut area = Vec::new();
+    for field in [ptr, reply.len() as i32, kind] {
+        area.extend(field.to_le_bytes());
+    }
+    let data = memory.data_mut(&mut caller);
+    data[ptr as usize..ptr as usize + reply.len()].copy_from_slice(reply.as_bytes());
+    data[result as usize..result as usize + 12].copy_from_slice(&area);
+    Ok(())
+}
+
+/// Defines every import of the module
+fn define_imports(linker: &mut Linker<Host>, module: &Module) {
+    for import in module.imports() {
+        let ExternType::Func(ty) = import.ty() else {
+            panic!("unexpected import {}", import.name());
+        };
+        let ty: FuncType = ty.clone();
+        let results: Vec<ValType> = ty.results().to_vec();
+        let (module_name, name) = (import.module(), import.name());
+        match (module_name, name) {
+            ("env", "print") => linker.func_new(module_name, name, ty, |mut caller, params, _| {
+                let memory = caller_memory(&caller);
+                let message = utf8(
+                    memory.data(&caller),
+                    params[0].i32().unwrap(),
+                    params[1].i32().unwrap(),
+                );
+                caller.data_mut().printed.push(message);
+                Ok(())
+            }),
+            ("env", "extern_func_call") => {
+                linker.func_new(module_name, name, ty, |caller, params, _| {
+                    extern_func_call(caller, params)
+                })
+            }
+            ("env", "__getrandom_custom") => {
+                linker.func_new(module_name, name, ty, |mut caller, params, results| {
+                    let (ptr, len) = (params[0].i32().unwrap(), params[1].i32().unwrap());
+                    let memory = caller_memory(&caller);
+                    let bytes = caller.data_mut().random_bytes(len as usize);
+                    memory.write(&mut caller, ptr as usize, &bytes).unwrap();
+                    results[0] = Val::I32(0);
+                    Ok(())
+                })
+            }
+            ("wasi_snapshot_preview1", name) => define_wasi_import(linker, name, ty),
+            // biscuit-auth measures the authorizer run time with `performance.now()`
+            ("__wbindgen_placeholder__", name) if name.starts_with("__wbg_performancenow_") => {
+                linker.func_new(module_name, name, ty, |caller, _, results| {
+                    let start = caller.data().start.unwrap();
+                    results[0] = Val::F64(F64::from(start.elapsed().as_secs_f64() * 1000.0));
+                    Ok(())
+                })
+            }
+            ("__wbindgen_placeholder__", "__wbindgen_throw") => {
+                linker.func_new(module_name, name, ty, |caller, params, _| {
+                    let memory = caller_memory(&caller);
+                    let message = utf8(
+                        memory.data(&caller),
+                        params[0].i32().unwrap(),
+                        params[1].i32().unwrap(),
+                    );
+                    Err(wasmi::Error::new(message))
+                })
+            }
+            // wasm-bindgen glue, never called outside of JavaScript hosts
+            ("__wbindgen_placeholder__" | "__wbindgen_externref_xform__", _) => {
+                linker.func_new(module_name, name, ty, move |_, _, out| {
+                    for (out, ty) in out.iter_mut().zip(&results) {
+                        *out = zero(ty);
+                    }
+                    Ok(())
+                })
+            }
+            (module_name, name) => panic!("unexpected import {module_name}.{name}"),
+        }
+        .unwrap();
+    }
+}
+
+/// WASI errno returned by the imports
+const ERRNO_SUCCESS: i32 = 0;
+const ERRNO_INVAL: i32 = 28;
+
+/// Defines the WASI preview 1 functions imported by the `wasm32-wasip1` module
+fn define_wasi_import<'a>(
+    linker: &'a mut Linker<Host>,
+    name: &str,
+    ty: FuncType,
+) -> Result<&'a mut Linker<Host>, wasmi::errors::LinkerError> {
+    let module_name = "wasi_snapshot_preview1";
+    match name {
+        "random_get" => linker.func_new(module_name, name, ty, |mut caller, params, results| {
+            let (ptr, len) = (params[0].i32().unwrap(), params[1].i32().unwrap());
+            let memory = caller_memory(&caller);
+            let bytes = caller.data_mut().random_bytes(len as usize);
+            memory.write(&mut caller, ptr as usize, &bytes).unwrap();
+            results[0] = Val::I32(ERRNO_SUCCESS);
+            Ok(())
+        }),
+        // no environment variables
+        "environ_sizes_get" => {
+            linker.func_new(module_name, name, ty, |mut caller, params, results| {
+                let memory = caller_memory(&caller);
+                for ptr in [params[0].i32().unwrap(), params[1].i32().unwrap()] {
+                    memory
+                        .write(&mut caller, ptr as usize, &0u32.to_le_bytes())
+                        .unwrap();
+                }
+                results[0] = Val::I32(ERRNO_SUCCESS);
+                Ok(())
+            })
+        }
+        "environ_get" => linker.func_new(module_name, name, ty, |_, _, results| {
+            results[0] = Val::I32(ERRNO_SUCCESS);
+            Ok(())
+        }),
+        // realtime (0) and monotonic (1) clocks, in nanoseconds
+        "clock_time_get" => {
+            linker.func_new(module_name, name, ty, |mut caller, params, results| {
+                let nanos = match params[0].i32().unwrap() {
+                    0 => SystemTime::now().duration_since(UNIX_EPOCH).unwrap(),
+                    1 => caller.data().start.unwrap().elapsed(),
+                    _ => {
+                        results[0] = Val::I32(ERRNO_INVAL);
+                        return Ok(());
+                    }
+                }
+                .as_nanos() as u64;
+                let memory = caller_memory(&caller);
+                let ptr = params[2].i32().unwrap();
+                memory
+                    .write(&mut caller, ptr as usize, &nanos.to_le_bytes())
+                    .unwrap();
+                results[0] = Val::I32(ERRNO_SUCCESS);
+                Ok(())
+            })
+        }
+        // stdout and stderr, recorded in `Host::output`
+        "fd_write" => linker.func_new(module_name, name, ty, |mut caller, params, results| {
+            let [fd, iovs, iovs_len, written] = params
+                .iter()
+                .map(|param| param.i32().unwrap())
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap();
+            if fd != 1 && fd != 2 {
+                results[0] = Val::I32(ERRNO_INVAL);
+                return Ok(());
+            }
+            let memory = caller_memory(&caller);
+            let mut bytes: Vec<u8> = Vec::new();
+            {
+                let data = memory.data(&caller);
+                for iov in 0..iovs_len as usize {
+                    let iov = iovs as usize + iov * 8;
+                    let (ptr, len) = (
+                        read_u32(data, iov) as usize,
+                        read_u32(data, iov + 4) as usize,
+                    );
+                    bytes.extend(&data[ptr..ptr + len]);
+                }
+            }
+            let len = bytes.len() as u32;
+            caller.data_mut().output.extend(bytes);
+            memory
+                .write(&mut caller, written as usize, &len.to_le_bytes())
+                .unwrap();
+            results[0] = Val::I32(ERRNO_SUCCESS);
+            Ok(())
+        }),
+        "proc_exit" => linker.func_new(module_name, name, ty, |_, params, _| {
+            Err(wasmi::Error::new(format!(
+                "proc_exit({})",
+                params[0].i32().unwrap()
+            )))
+        }),
+        name => panic!("unexpected import {module_name}.{name}"),
+    }
+}
+
+impl Host {
+    /// xorshift, seeded from the clock: good enough for tests
+    fn random_bytes(&mut self, len: usize) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(len);
+        for _ in 0..len {
+            self.rng ^= self.rng << 13;
+            self.rng ^= self.rng >> 7;
+            self.rng ^= self.rng << 17;
+            bytes.push(self.rng as u8);
+        }
+        bytes
+    }
+}
+
+impl Shim {
+    /// Loads the `wasm32-unknown-unknown` module
+    pub fn load() -> Self {
+        Self::load_target(Target::Unknown)
+    }
+
+    pub fn load_target(target: Target) -> Self {
+        let engine = Engine::default();
+        let module =
+            Module::new(&engine, &std::fs::read(module_path(target)).unwrap()[..]).unwrap();
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos() as u64;
+        let host = Host {
+            rng: seed | 1,
+            start: Some(Instant::now()),
+            ..Host::default()
+        };
+        let mut store = Store::new(&engine, host);
+        let mut linker = Linker::new(&engine);
+        define_imports(&mut linker, &module);
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .unwrap()
+            .start(&mut store)
+            .unwrap();
+        let memory = instance.get_memory(&store, "memory").unwrap();
+        let exports = module
+            .exports()
+            .filter(|export| matches!(export.ty(), ExternType::Func(_)))
+            .map(|export| export.name().to_string())
+            .collect();
+
+        let mut shim = Shim {
+            store,
+            instance,
+            memory,
+            exports,
+            target,
+            ret: 0,
+        };
+        shim.ret = shim.raw("get_return_area", &[])[0].i32().unwrap();
+        shim
+    }
+
+    pub fn host(&self) -> &Host {
+        self.store.data()
+    }
+
+    /// Operations exported by the module, without the allocation functions and the wasm-bindgen glue
+    pub fn operations(&self) -> BTreeSet<String> {
+        self.exports
+            .iter()
+            .filter(|name| !INTERNAL_EXPORTS.contains(&name.as_str()))
+            .filter(|name| !name.starts_with("__"))
+            .cloned()
+            .collect()
+    }
+
+    /// Type of an exported function
+    pub fn func_type(&self, name: &str) -> Option<FuncType> {
+        let func = self.instance.get_func(&self.store, name)?;
+        Some(func.ty(&self.store))
+    }
+
+    fn func(&self, name: &str) -> Func {
+        self.instance
+            .get_func(&self.store, name)
+            .unwrap_or_else(|| panic!("{name} is not exported"))
+    }
+
+    /// Calls an export with raw values
+    pub fn raw(&mut self, name: &str, params: &[Val]) -> Vec<Val> {
+        let func = self.func(name);
+        let ty = func.ty(&self.store);
+        let mut results: Vec<Val> = ty.results().iter().map(zero).collect();
+        func.call(&mut self.store, params, &mut results)
+            .unwrap_or_else(|e| panic!("{name} trapped: {e}"));
+        self.store.data_mut().called.insert(name.to_string());
+        results
+    }
+
+    /// Copies bytes into a buffer from `malloc(len, 1)`
+    fn write_input(&mut self, bytes: &[u8]) -> i32 {
+        if bytes.is_empty() {
+            // a dangling pointer is enough for an empty slice, `malloc(0, 1)` is not allowed
+            return 1;
+        }
+        let malloc = self.target.malloc();
+        let ptr = self.raw(malloc, &[Val::I32(bytes.len() as i32), Val::I32(1)])[0]
+            .i32()
+            .unwrap();
+        self.memory
+            .write(&mut self.store, ptr as usize, bytes)
+            .unwrap();
+        ptr
+    }
+
+    fn read_area(&self) -> (u32, u32, Kind) {
+        let data = self.memory.data(&self.store);
+        let ret = self.ret as usize;
+        (
+            read_u32(data, ret),
+            read_u32(data, ret + 4),
+            Kind::from_raw(read_u32(data, ret + 8)),
+        )
+    }
+
+    fn read_bytes(&self, ptr: u32, len: u32) -> Vec<u8> {
+        self.memory.data(&self.store)[ptr as usize..(ptr + len) as usize].to_vec()
+    }
+
+    fn call_with(
+        &mut self,
+        name: &str,
+        args: &[Arg],
+        release: impl FnOnce(&mut Self, u32, u32),
+    ) -> Result<Output, Failure> {
+        let mut params = vec![Val::I32(self.ret)];
+        let mut inputs = Vec::new();
+        for arg in args {
+            match arg {
+                Arg::Str(value) => inputs.push(value.as_bytes()),
+                Arg::Bytes(value) => inputs.push(value),
+                _ => {}
+            }
+        }
+        let mut buffers = Vec::new();
+        for input in inputs {
+            let ptr = self.write_input(input);
+            buffers.push((ptr, input.len() as i32));
+        }
+        let mut buffer = buffers.iter();
+        for arg in args {
+            match arg {
+                Arg::Str(_) | Arg::Bytes(_) => {
+                    let (ptr, len) = buffer.next().unwrap();
+                    params.extend([Val::I32(*ptr), Val::I32(*len)]);
+                }
+                Arg::U32(value) | Arg::Ptr(value) => params.push(Val::I32(*value as i32)),
+                Arg::U64(value) => params.push(Val::I64(*value as i64)),
+            }
+        }
+        self.raw(name, &params);
+
+        // the inputs are borrowed by the call
+        for (ptr, len) in buffers {
+            if len != 0 {
+                let free = self.target.free();
+                self.raw(free, &[Val::I32(ptr), Val::I32(len), Val::I32(1)]);
+            }
+        }
+
+        let (data, len, kind) = self.read_area();
+        let bytes = self.read_bytes(data, len);
+        if len != 0 {
+            release(self, data, len);
+        }
+        match kind {
+            Kind::Ok | Kind::OkNone => Ok(Output { kind, data, bytes }),
+            kind => Err(Failure {
+                kind,
+                message: String::from_utf8(bytes).unwrap(),
+            }),
+        }
+    }
+
+    /// Calls an export and releases the returned buffer with `wasm_result_free`
+    pub fn call(&mut self, name: &str, args: &[Arg]) -> Result<Output, Failure> {
+        self.call_with(name, args, |shim, _, _| {
+            let ret = shim.ret;
+            shim.raw("wasm_result_free", &[Val::I32(ret)]);
+        })
+    }
+
+    /// Calls an export returning a secret and releases it with `secret_string_free`
+    pub fn call_secret(&mut self, name: &str, args: &[Arg]) -> Result<Output, Failure> {
+        self.call_with(name, args, |shim, ptr, len| {
+            shim.raw(
+                "secret_string_free",
+                &[Val::I32(ptr as i32), Val::I32(len as i32)],
+            );
+        })
+    }
+
+    /// Calls an export expected to succeed
+    pub fn ok(&mut self, name: &str, args: &[Arg]) -> Output {
+        self.call(name, args)
+            .unwrap_or_else(|failure| panic!("{name} failed: {failure:?}"))
+    }
+
+    /// Calls an export expected to fail
+    pub fn err(&mut self, name: &str, args: &[Arg]) -> Failure {
+        match self.call(name, args) {
+            Ok(output) => panic!("{name} succeeded: {output:?}"),
+            Err(failure) => failure,
+        }
+    }
+
+    /// Linear memory of the module
+    pub fn memory(&mut self) -> &mut [u8] {
+        self.memory.data_mut(&mut self.store)
+    }
+
+    /// Size of the linear memory, to check that repeated calls do not leak
+    pub fn memory_size(&self) -> usize {
+        self.memory.data(&self.store).len()
+    }
+}
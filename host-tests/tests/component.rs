@@ -0,0 +1,178 @@
+//! Runs the `wasm32-wasip2` component through the bindings generated from `wit/shim.wit`
+
+use exports::biscuit::shim::types::{Error, SignatureAlgorithm};
+use host_tests::component_path;
+use wasmtime::component::{Component, Linker, ResourceAny, ResourceTable};
+use wasmtime::{Engine, Store};
+use wasmtime_wasi::{IoView, WasiCtx, WasiCtxBuilder, WasiView};
+
+wasmtime::component::bindgen!({
+    world: "shim",
+    path: "../wit",
+    additional_derives: [PartialEq],
+});
+
+const MAX_TIME: u64 = 10_000;
+
+/// WASI state of the store, the standard library of the component imports WASI 0.2
+///
+/// stderr is inherited so that the message of a panic in the component is shown
+struct Host {
+    ctx: WasiCtx,
+    table: ResourceTable,
+}
+
+impl IoView for Host {
+    fn table(&mut self) -> &mut ResourceTable {
+        &mut self.table
+    }
+}
+
+impl WasiView for Host {
+    fn ctx(&mut self) -> &mut WasiCtx {
+        &mut self.ctx
+    }
+}
+
+fn instantiate() -> (Store<Host>, Shim) {
+    let engine = Engine::default();
+    let component = Component::from_file(&engine, component_path()).unwrap();
+    let mut linker = Linker::new(&engine);
+    wasmtime_wasi::add_to_linker_sync(&mut linker).unwrap();
+    let host = Host {
+        ctx: WasiCtxBuilder::new().inherit_stderr().build(),
+        table: ResourceTable::new(),
+    };
+    let mut store = Store::new(&engine, host);
+    let shim = Shim::instantiate(&mut store, &component, &linker).unwrap();
+    (store, shim)
+}
+
+#[test]
+fn token_flow() {
+    let (mut store, shim) = instantiate();
+    let types = shim.biscuit_shim_types();
+    let store = &mut store;
+
+    let root = types
+        .key_pair()
+        .call_constructor(&mut *store, SignatureAlgorithm::Ed25519)
+        .unwrap();
+    let public_key = types.key_pair().call_public_key(&mut *store, root).unwrap();
+    let public_hex = types
+        .public_key()
+        .call_to_hex(&mut *store, public_key)
+        .unwrap();
+    assert!(public_hex.starts_with("ed25519/"), "{public_hex}");
+
+    let builder = types
+        .biscuit_builder()
+        .call_constructor(&mut *store)
+        .unwrap();
+    let add_code = |store: &mut Store<Host>, code: &str| {
+        types
+            .biscuit_builder()
+            .call_add_code(store, builder, code)
+            .unwrap()
+    };
+    add_code(store, r#"right("file1", "read");"#).unwrap();
+    // unlike the exports, a failed add-code leaves the builder as it was
+    let error = add_code(store, "right(").unwrap_err();
+    assert!(
+        matches!(&error, Error::Biscuit(message) if message.contains("Language")),
+        "{error:?}"
+    );
+    add_code(store, r#"right("file2", "read");"#).unwrap();
+    // add-fact validates the fact before touching the builder
+    let error = types
+        .biscuit_builder()
+        .call_add_fact(&mut *store, builder, "right(")
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        matches!(&error, Error::Biscuit(message) if message.contains("Language")),
+        "{error:?}"
+    );
+    types
+        .biscuit_builder()
+        .call_add_fact(&mut *store, builder, r#"right("file3", "read")"#)
+        .unwrap()
+        .unwrap();
+    let error = types
+        .biscuit_builder()
+        .call_check_expiration_date(&mut *store, builder, u64::MAX)
+        .unwrap()
+        .unwrap_err();
+    assert!(
+        matches!(&error, Error::Biscuit(message) if message.contains("ConversionError")),
+        "{error:?}"
+    );
+    let code = types
+        .biscuit_builder()
+        .call_to_string(&mut *store, builder)
+        .unwrap();
+    assert!(code.contains(r#"right("file1", "read");"#), "{code}");
+    assert!(code.contains(r#"right("file2", "read");"#), "{code}");
+    assert!(code.contains(r#"right("file3", "read");"#), "{code}");
+
+    let biscuit = types
+        .biscuit_builder()
+        .call_build_with_key_pair(&mut *store, builder, root)
+        .unwrap()
+        .unwrap();
+    let base64 = types
+        .biscuit()
+        .call_to_base64(&mut *store, biscuit)
+        .unwrap()
+        .unwrap();
+
+    let authorize = |store: &mut Store<Host>, code: &str| -> Result<u32, Error> {
+        let builder = types
+            .authorizer_builder()
+            .call_constructor(&mut *store)
+            .unwrap();
+        types
+            .authorizer_builder()
+            .call_add_code(&mut *store, builder, code)
+            .unwrap()?;
+        let authorizer = types
+            .authorizer_builder()
+            .call_build(&mut *store, builder, biscuit)
+            .unwrap()?;
+        let outcome = types
+            .authorizer()
+            .call_authorize(&mut *store, authorizer, MAX_TIME)
+            .unwrap();
+        drop_resource(store, authorizer);
+        outcome
+    };
+    assert_eq!(
+        authorize(store, r#"allow if right("file2", "read");"#),
+        Ok(0)
+    );
+    let error = authorize(store, r#"allow if right("file1", "write");"#).unwrap_err();
+    assert!(
+        matches!(&error, Error::Biscuit(message) if message.starts_with(r#"{"FailedLogic":"#)),
+        "{error:?}"
+    );
+
+    let policy = types
+        .call_verify_and_authorize(
+            &mut *store,
+            &base64,
+            &public_hex,
+            r#"allow if right("file1", "read");"#,
+            "",
+            r#"{"max_time": 10000}"#,
+        )
+        .unwrap();
+    assert_eq!(policy, Ok(0));
+
+    for resource in [biscuit, public_key, root] {
+        drop_resource(store, resource);
+    }
+}
+
+fn drop_resource(store: &mut Store<Host>, resource: ResourceAny) {
+    resource.resource_drop(store).unwrap();
+}
@@ -0,0 +1,768 @@
+//! Calls every export of the module from the host, success and error paths
+
+use host_tests::{Arg::*, Failure, Kind, Shim};
+use serde_json::{Value, json};
+use wasmi::Val;
+
+const ED25519: u32 = 0;
+const SECP256R1: u32 = 1;
+const MAX_TIME: u64 = 10_000;
+const LIMITS: &str = r#"{"max_time": 10000}"#;
+
+fn json_error(failure: &Failure) -> Value {
+    assert_eq!(failure.kind, Kind::ErrBiscuit, "{failure:?}");
+    serde_json::from_str(&failure.message).unwrap()
+}
+
+/// Builds a token with a `right("file1", "read")` authority fact, returns the keypair and the token
+fn token(shim: &mut Shim, algorithm: u32) -> (u32, u32) {
+    let root = shim.ok("keypair_new", &[Ptr(algorithm)]).handle();
+    let builder = shim.ok("biscuit_builder_new", &[]).handle();
+    shim.ok(
+        "biscuit_builder_add_code",
+        &[Ptr(builder), Str(r#"right("file1", "read");"#)],
+    )
+    .unit();
+    let biscuit = shim
+        .ok(
+            "biscuit_builder_build_with_key_pair",
+            &[Ptr(builder), Ptr(root)],
+        )
+        .handle();
+    (root, biscuit)
+}
+
+fn authorize(shim: &mut Shim, code: &str, biscuit: u32) -> Result<u32, Failure> {
+    let builder = shim.ok("authorizer_builder_new", &[]).handle();
+    shim.ok("authorizer_builder_add_code", &[Ptr(builder), Str(code)])
+        .unit();
+    let authorizer = shim
+        .ok("authorizer_builder_build", &[Ptr(builder), Ptr(biscuit)])
+        .handle();
+    let run_limits = shim.ok("authorizer_run_limits", &[U64(MAX_TIME)]).handle();
+    let outcome = shim
+        .call("authorizer_authorize", &[Ptr(authorizer), Ptr(run_limits)])
+        .map(|output| output.number());
+    shim.ok("authorizer_drop", &[Ptr(authorizer)]).unit();
+    outcome
+}
+
+fn keys(shim: &mut Shim) {
+    for algorithm in [ED25519, SECP256R1] {
+        let keypair = shim.ok("keypair_new", &[Ptr(algorithm)]).handle();
+        let public_key = shim.ok("keypair_public_key", &[Ptr(keypair)]).handle();
+        let public_hex = shim.ok("public_key_to_hex", &[Ptr(public_key)]).string();
+        let prefix = ["ed25519/", "secp256r1/"][algorithm as usize];
+        assert!(public_hex.starts_with(prefix), "{public_hex}");
+
+        let private_key = shim.ok("keypair_private_key", &[Ptr(keypair)]).handle();
+        let private_hex = shim
+            .call_secret("private_key_to_hex", &[Ptr(private_key)])
+            .unwrap()
+            .string();
+        let reloaded = shim
+            .ok("private_key_from_hex", &[Str(&private_hex)])
+            .handle();
+        let same = shim
+            .ok("keypair_from_private_key", &[Ptr(reloaded)])
+            .handle();
+        let same_public = shim.ok("keypair_public_key", &[Ptr(same)]).handle();
+        assert_eq!(
+            shim.ok("public_key_to_hex", &[Ptr(same_public)]).string(),
+            public_hex
+        );
+        // the algorithm argument only applies to unprefixed keys
+        let parsed = shim
+            .ok("public_key_from_hex", &[Str(&public_hex), Ptr(ED25519)])
+            .handle();
+        assert_eq!(
+            shim.ok("public_key_to_hex", &[Ptr(parsed)]).string(),
+            public_hex
+        );
+
+        let signature = shim
+            .ok("keypair_sign", &[Ptr(keypair), Bytes(b"payload")])
+            .bytes;
+        assert!(!signature.is_empty());
+
+        // public keys have no drop function
+        for (name, handle) in [
+            ("keypair_drop", same),
+            ("private_key_drop", reloaded),
+            ("private_key_drop", private_key),
+            ("keypair_drop", keypair),
+        ] {
+            shim.ok(name, &[Ptr(handle)]).unit();
+        }
+    }
+
+    json_error(&shim.err("private_key_from_hex", &[Str("not hex")]));
+    json_error(&shim.err("public_key_from_hex", &[Str(""), Ptr(SECP256R1)]));
+
+    // seeds and passphrases are deterministic
+    let mut public_keys = Vec::new();
+    for _ in 0..2 {
+        let from_seed = shim
+            .ok("keypair_from_seed", &[Ptr(ED25519), Bytes(&[7; 32])])
+            .handle();
+        let from_passphrase = shim
+            .ok(
+                "keypair_from_passphrase",
+                &[Ptr(SECP256R1), Str("correct horse"), Bytes(b"salt")],
+            )
+            .handle();
+        for keypair in [from_seed, from_passphrase] {
+            let public_key = shim.ok("keypair_public_key", &[Ptr(keypair)]).handle();
+            public_keys.push(shim.ok("public_key_to_hex", &[Ptr(public_key)]).string());
+            shim.ok("keypair_drop", &[Ptr(keypair)]).unit();
+        }
+    }
+    assert_eq!(public_keys[..2], public_keys[2..]);
+    json_error(&shim.err("keypair_from_seed", &[Ptr(ED25519), Bytes(&[7; 3])]));
+}
+
+fn builders(shim: &mut Shim) {
+    let block = shim.ok("block_builder_new", &[]).handle();
+    shim.ok("block_builder_add_code", &[Ptr(block), Str("fact(1);")])
+        .unit();
+    shim.ok(
+        "block_builder_check_expiration_date",
+        &[Ptr(block), U64(1_700_000_000)],
+    )
+    .unit();
+    json_error(&shim.err(
+        "block_builder_check_expiration_date",
+        &[Ptr(block), U64(u64::MAX)],
+    ));
+    for (name, value) in [
+        ("block_builder_check_resource", "file1"),
+        ("block_builder_check_resource_prefix", "/files/"),
+        ("block_builder_check_resource_suffix", ".txt"),
+        ("block_builder_check_operation", "read"),
+        ("block_builder_set_context", "attenuated"),
+    ] {
+        shim.ok(name, &[Ptr(block), Str(value)]).unit();
+    }
+    let code = shim.ok("block_builder_to_string", &[Ptr(block)]).string();
+    assert!(code.contains("fact(1);"), "{code}");
+    shim.ok("block_builder_drop", &[Ptr(block)]).unit();
+
+    // a failed add_code drops the builder in place, the pointer must not be used again
+    let broken = shim.ok("block_builder_new", &[]).handle();
+    json_error(&shim.err("block_builder_add_code", &[Ptr(broken), Str("fact(")]));
+
+    let keypair = shim.ok("keypair_new", &[Ptr(ED25519)]).handle();
+    let public_key = shim.ok("keypair_public_key", &[Ptr(keypair)]).handle();
+    let builder = shim.ok("biscuit_builder_new", &[]).handle();
+    for (name, value) in [
+        ("biscuit_builder_add_code", "user(1);"),
+        ("biscuit_builder_add_fact", r#"right("file1", "read")"#),
+        ("biscuit_builder_add_rule", "u($id) <- user($id)"),
+        ("biscuit_builder_add_check", "check if user(1)"),
+    ] {
+        shim.ok(name, &[Ptr(builder), Str(value)]).unit();
+    }
+    for name in [
+        "biscuit_builder_add_scope_authority",
+        "biscuit_builder_add_scope_previous",
+    ] {
+        shim.ok(name, &[Ptr(builder)]).unit();
+    }
+    shim.ok(
+        "biscuit_builder_add_scope_public_key",
+        &[Ptr(builder), Ptr(public_key)],
+    )
+    .unit();
+    shim.ok(
+        "biscuit_builder_check_expiration_date",
+        &[Ptr(builder), U64(1_700_000_000)],
+    )
+    .unit();
+    json_error(&shim.err(
+        "biscuit_builder_check_expiration_date",
+        &[Ptr(builder), U64(u64::MAX)],
+    ));
+    for (name, value) in [
+        ("biscuit_builder_check_resource", "file1"),
+        ("biscuit_builder_check_resource_prefix", "/files/"),
+        ("biscuit_builder_check_resource_suffix", ".txt"),
+        ("biscuit_builder_check_operation", "read"),
+        ("biscuit_builder_set_context", "authority"),
+    ] {
+        shim.ok(name, &[Ptr(builder), Str(value)]).unit();
+    }
+    shim.ok("biscuit_builder_set_root_key_id", &[Ptr(builder), U32(3)])
+        .unit();
+
+    // the deprecated constructor is an alias of biscuit_builder_new
+    let other = shim.ok("biscuit_builder", &[]).handle();
+    shim.ok(
+        "biscuit_builder_add_fact",
+        &[Ptr(other), Str("merged(true)")],
+    )
+    .unit();
+    shim.ok("biscuit_builder_merge", &[Ptr(builder), Ptr(other)])
+        .unit();
+
+    let code = shim
+        .ok("biscuit_builder_to_string", &[Ptr(builder)])
+        .string();
+    assert!(code.contains("merged(true);"), "{code}");
+    let content: Value =
+        serde_json::from_str(&shim.ok("biscuit_builder_to_json", &[Ptr(builder)]).string())
+            .unwrap();
+    assert_eq!(content["facts"].as_array().unwrap().len(), 3, "{content}");
+    let description = json!({ "facts": ["user(2)"], "rules": [], "checks": [] }).to_string();
+    let copy = shim
+        .ok("biscuit_builder_from_json", &[Str(&description)])
+        .handle();
+    json_error(&shim.err("biscuit_builder_from_json", &[Str("{")]));
+
+    let biscuit = shim
+        .ok(
+            "biscuit_builder_build_with_key_pair",
+            &[Ptr(builder), Ptr(keypair)],
+        )
+        .handle();
+    assert_eq!(
+        shim.ok("biscuit_root_key_id", &[Ptr(biscuit)]).option(),
+        Some(3)
+    );
+    assert_eq!(
+        shim.ok("biscuit_block_context", &[Ptr(biscuit), U32(0)])
+            .string(),
+        "authority"
+    );
+    json_error(&shim.err("biscuit_block_context", &[Ptr(biscuit), U32(9)]));
+
+    let private_key = shim.ok("keypair_private_key", &[Ptr(keypair)]).handle();
+    let unnamed = shim
+        .ok(
+            "biscuit_builder_build_with_private_key",
+            &[Ptr(copy), Ptr(private_key)],
+        )
+        .handle();
+    assert_eq!(
+        shim.ok("biscuit_root_key_id", &[Ptr(unnamed)]).option(),
+        None
+    );
+
+    // a failed add_fact drops the builder in place as well
+    let failing = shim.ok("biscuit_builder_new", &[]).handle();
+    json_error(&shim.err("biscuit_builder_add_fact", &[Ptr(failing), Str("fact(")]));
+    let empty = shim.ok("biscuit_builder_new", &[]).handle();
+    shim.ok("biscuit_builder_drop", &[Ptr(empty)]).unit();
+
+    let authorizer = shim.ok("authorizer_builder_new", &[]).handle();
+    shim.ok(
+        "authorizer_builder_add_code",
+        &[Ptr(authorizer), Str("allow if user(1);")],
+    )
+    .unit();
+    let lint = shim
+        .ok("authorizer_builder_lint", &[Ptr(authorizer)])
+        .string();
+    serde_json::from_str::<Value>(&lint).unwrap();
+    let content: Value = serde_json::from_str(
+        &shim
+            .ok("authorizer_builder_to_json", &[Ptr(authorizer)])
+            .string(),
+    )
+    .unwrap();
+    assert_eq!(content["policies"], json!(["allow if user(1)"]));
+    let description = json!({ "policies": ["allow if user(1)"] }).to_string();
+    let other = shim
+        .ok("authorizer_builder_from_json", &[Str(&description)])
+        .handle();
+    json_error(&shim.err(
+        "authorizer_builder_from_json",
+        &[Str(r#"{"policies": ["allow if"]}"#)],
+    ));
+    shim.ok("authorizer_builder_merge", &[Ptr(authorizer), Ptr(other)])
+        .unit();
+    let code = shim
+        .ok("authorizer_builder_to_string", &[Ptr(authorizer)])
+        .string();
+    assert_eq!(code.matches("allow if user(1);").count(), 2, "{code}");
+    json_error(&shim.err(
+        "authorizer_builder_add_code",
+        &[Ptr(authorizer), Str("allow if")],
+    ));
+
+    let drop_builder = shim.ok("authorizer_builder_new", &[]).handle();
+    shim.ok("authorizer_builder_drop", &[Ptr(drop_builder)])
+        .unit();
+    for (name, handle) in [
+        ("biscuit_drop", biscuit),
+        ("biscuit_drop", unnamed),
+        ("private_key_drop", private_key),
+        ("keypair_drop", keypair),
+    ] {
+        shim.ok(name, &[Ptr(handle)]).unit();
+    }
+}
+
+fn tokens(shim: &mut Shim) {
+    let (root, biscuit) = token(shim, SECP256R1);
+    let public_key = shim.ok("keypair_public_key", &[Ptr(root)]).handle();
+    let public_hex = shim.ok("public_key_to_hex", &[Ptr(public_key)]).string();
+
+    let bytes = shim.ok("biscuit_to_bytes", &[Ptr(biscuit)]).bytes;
+    let base64 = shim.ok("biscuit_to_base64", &[Ptr(biscuit)]).string();
+    let from_bytes = shim
+        .ok("biscuit_from_bytes", &[Bytes(&bytes), Ptr(public_key)])
+        .handle();
+    let from_base64 = shim
+        .ok("biscuit_from_base64", &[Str(&base64), Ptr(public_key)])
+        .handle();
+    assert_eq!(
+        shim.ok("biscuit_root_key_id", &[Ptr(from_bytes)]).option(),
+        None
+    );
+    json_error(&shim.err("biscuit_from_bytes", &[Bytes(&bytes[1..]), Ptr(public_key)]));
+
+    // tokens signed by another root key are rejected
+    let other = shim.ok("keypair_new", &[Ptr(SECP256R1)]).handle();
+    let other_public_key = shim.ok("keypair_public_key", &[Ptr(other)]).handle();
+    json_error(&shim.err(
+        "biscuit_from_base64",
+        &[Str(&base64), Ptr(other_public_key)],
+    ));
+
+    // attenuation
+    let block = shim.ok("block_builder_new", &[]).handle();
+    shim.ok(
+        "block_builder_add_code",
+        &[Ptr(block), Str(r#"check if operation("read");"#)],
+    )
+    .unit();
+    let attenuated = shim
+        .ok("biscuit_append", &[Ptr(from_base64), Ptr(block)])
+        .handle();
+    assert_eq!(
+        authorize(
+            shim,
+            r#"operation("read"); allow if right("file1", "read");"#,
+            attenuated
+        )
+        .unwrap(),
+        0
+    );
+    let failure = authorize(
+        shim,
+        r#"operation("write"); allow if right("file1", "read");"#,
+        attenuated,
+    )
+    .unwrap_err();
+    assert!(
+        json_error(&failure).get("FailedLogic").is_some(),
+        "{failure:?}"
+    );
+
+    // third party blocks
+    let request = shim
+        .ok("biscuit_third_party_request", &[Ptr(attenuated)])
+        .handle();
+    let request_base64 = shim
+        .ok("third_party_request_to_base64", &[Ptr(request)])
+        .string();
+    shim.ok("third_party_request_drop", &[Ptr(request)]).unit();
+    let request = shim
+        .ok("third_party_request_from_base64", &[Str(&request_base64)])
+        .handle();
+    json_error(&shim.err("third_party_request_from_base64", &[Str("%%")]));
+
+    let external = shim.ok("keypair_new", &[Ptr(ED25519)]).handle();
+    let external_private = shim.ok("keypair_private_key", &[Ptr(external)]).handle();
+    let external_public = shim.ok("keypair_public_key", &[Ptr(external)]).handle();
+    let external_hex = shim
+        .ok("public_key_to_hex", &[Ptr(external_public)])
+        .string();
+    let block = shim.ok("block_builder_new", &[]).handle();
+    shim.ok(
+        "block_builder_add_code",
+        &[Ptr(block), Str("group(\"admin\");")],
+    )
+    .unit();
+    let third_party_block = shim
+        .ok(
+            "third_party_request_create_block",
+            &[Ptr(request), Ptr(external_private), Ptr(block)],
+        )
+        .handle();
+    assert!(
+        !shim
+            .ok("third_party_block_to_base64", &[Ptr(third_party_block)])
+            .string()
+            .is_empty()
+    );
+    let with_third_party = shim
+        .ok(
+            "biscuit_append_third_party",
+            &[
+                Ptr(attenuated),
+                Ptr(external_public),
+                Ptr(third_party_block),
+            ],
+        )
+        .handle();
+    let code = format!(r#"operation("read"); allow if group("admin") trusting {external_hex};"#);
+    assert_eq!(authorize(shim, &code, with_third_party).unwrap(), 0);
+
+    let unused = shim
+        .ok("biscuit_third_party_request", &[Ptr(attenuated)])
+        .handle();
+    let block = shim.ok("block_builder_new", &[]).handle();
+    let unused_block = shim
+        .ok(
+            "third_party_request_create_block",
+            &[Ptr(unused), Ptr(external_private), Ptr(block)],
+        )
+        .handle();
+    shim.ok("third_party_block_drop", &[Ptr(unused_block)])
+        .unit();
+
+    // biscuit_authorizer gives an authorizer without policies
+    let authorizer = shim
+        .ok("biscuit_authorizer", &[Ptr(with_third_party)])
+        .handle();
+    let world = shim
+        .ok("authorizer_print_world", &[Ptr(authorizer)])
+        .string();
+    assert!(world.contains(r#"right("file1", "read")"#), "{world}");
+    let dump: Value =
+        serde_json::from_str(&shim.ok("authorizer_dump_json", &[Ptr(authorizer)]).string())
+            .unwrap();
+    assert!(dump["facts"].is_array(), "{dump}");
+    let run_limits = shim.ok("authorizer_run_limits", &[U64(MAX_TIME)]).handle();
+    json_error(&shim.err("authorizer_authorize", &[Ptr(authorizer), Ptr(run_limits)]));
+    shim.ok("authorizer_drop", &[Ptr(authorizer)]).unit();
+
+    // one call verification
+    let params = json!({ "operation": "read" }).to_string();
+    let verify = |shim: &mut Shim, token: &str, key: &str| {
+        shim.call(
+            "biscuit_verify_and_authorize",
+            &[
+                Str(token),
+                Str(key),
+                Str(r#"allow if right("file1", {operation});"#),
+                Str(&params),
+                Str(LIMITS),
+            ],
+        )
+    };
+    assert_eq!(verify(shim, &base64, &public_hex).unwrap().number(), 0);
+    json_error(&verify(shim, "invalid", &public_hex).unwrap_err());
+    json_error(&verify(shim, &base64, "ed25519/00").unwrap_err());
+
+    for (name, handle) in [
+        ("biscuit_drop", with_third_party),
+        ("biscuit_drop", attenuated),
+        ("biscuit_drop", from_base64),
+        ("biscuit_drop", from_bytes),
+        ("biscuit_drop", biscuit),
+        ("private_key_drop", external_private),
+        ("keypair_drop", external),
+        ("keypair_drop", other),
+        ("keypair_drop", root),
+    ] {
+        shim.ok(name, &[Ptr(handle)]).unit();
+    }
+}
+
+fn external_signature(shim: &mut Shim) {
+    let signer = shim.ok("keypair_new", &[Ptr(ED25519)]).handle();
+    let public_key = shim.ok("keypair_public_key", &[Ptr(signer)]).handle();
+
+    let unsigned = |shim: &mut Shim| {
+        let builder = shim.ok("biscuit_builder_new", &[]).handle();
+        shim.ok("biscuit_builder_add_fact", &[Ptr(builder), Str("user(1)")])
+            .unit();
+        shim.ok(
+            "biscuit_builder_prepare_external_signature",
+            &[Ptr(builder), Ptr(public_key)],
+        )
+        .handle()
+    };
+
+    let pending = unsigned(shim);
+    let payload = shim
+        .ok("unsigned_biscuit_signature_payload", &[Ptr(pending)])
+        .bytes;
+    let signature = shim
+        .ok("keypair_sign", &[Ptr(signer), Bytes(&payload)])
+        .bytes;
+    let biscuit = shim
+        .ok(
+            "unsigned_biscuit_finish",
+            &[Ptr(pending), Bytes(&signature)],
+        )
+        .handle();
+    assert_eq!(authorize(shim, "allow if user(1);", biscuit).unwrap(), 0);
+
+    // a wrong signature consumes the unsigned biscuit too
+    let pending = unsigned(shim);
+    json_error(&shim.err("unsigned_biscuit_finish", &[Ptr(pending), Bytes(&[0; 64])]));
+
+    let pending = unsigned(shim);
+    shim.ok("unsigned_biscuit_drop", &[Ptr(pending)]).unit();
+    shim.ok("biscuit_drop", &[Ptr(biscuit)]).unit();
+    shim.ok("keypair_drop", &[Ptr(signer)]).unit();
+}
+
+fn batch(shim: &mut Shim) {
+    let (root, biscuit) = token(shim, ED25519);
+    let public_key = shim.ok("keypair_public_key", &[Ptr(root)]).handle();
+    let bytes = shim.ok("biscuit_to_bytes", &[Ptr(biscuit)]).bytes;
+
+    let mut tokens = Vec::new();
+    for token in [&bytes[..], b"invalid"] {
+        tokens.extend((token.len() as u32).to_le_bytes());
+        tokens.extend(token);
+    }
+    let builder = shim.ok("authorizer_builder_new", &[]).handle();
+    shim.ok(
+        "authorizer_builder_add_code",
+        &[Ptr(builder), Str(r#"allow if right("file1", "read");"#)],
+    )
+    .unit();
+    let run_limits = shim.ok("authorizer_run_limits", &[U64(MAX_TIME)]).handle();
+    let outcomes = shim
+        .ok(
+            "authorize_batch",
+            &[
+                Ptr(builder),
+                Ptr(run_limits),
+                Bytes(&tokens),
+                Ptr(public_key),
+            ],
+        )
+        .bytes;
+    let outcomes: Vec<i32> = outcomes
+        .chunks(4)
+        .map(|outcome| i32::from_le_bytes(outcome.try_into().unwrap()))
+        .collect();
+    assert_eq!(outcomes, [0, -1]);
+
+    let truncated = &tokens[..tokens.len() - 1];
+    shim.err(
+        "authorize_batch",
+        &[
+            Ptr(builder),
+            Ptr(run_limits),
+            Bytes(truncated),
+            Ptr(public_key),
+        ],
+    );
+
+    shim.ok("authorizer_builder_drop", &[Ptr(builder)]).unit();
+    shim.ok("biscuit_drop", &[Ptr(biscuit)]).unit();
+    shim.ok("keypair_drop", &[Ptr(root)]).unit();
+}
+
+fn datalog(shim: &mut Shim) {
+    let kinds = [
+        ("fact(1); check if fact($x);", 0),
+        ("allow if true;", 1),
+        ("fact(1)", 2),
+        ("head($x) <- body($x)", 3),
+        ("check if fact(1)", 4),
+        ("deny if true", 5),
+    ];
+    for (code, kind) in kinds {
+        shim.ok("datalog_validate", &[Str(code), Ptr(kind)]).unit();
+    }
+    let errors = json_error(&shim.err("datalog_validate", &[Str("fact("), Ptr(0)]));
+    assert!(errors.is_array(), "{errors}");
+
+    let formatted = shim.ok("datalog_format", &[Str("fact( 1 ) ;")]).string();
+    assert_eq!(formatted.trim(), "fact(1);");
+    json_error(&shim.err("datalog_format", &[Str("fact(")]));
+}
+
+fn extern_funcs(shim: &mut Shim) {
+    let (root, biscuit) = token(shim, ED25519);
+    for (code, expected) in [
+        ("allow if 1.extern::add(2) == 3;", true),
+        ("allow if 1.extern::missing(2) == 3;", false),
+    ] {
+        let builder = shim.ok("authorizer_builder_new", &[]).handle();
+        shim.ok("authorizer_builder_add_code", &[Ptr(builder), Str(code)])
+            .unit();
+        for name in ["add", "missing"] {
+            shim.ok(
+                "authorizer_builder_register_extern_func",
+                &[Ptr(builder), Str(name)],
+            )
+            .unit();
+        }
+        let authorizer = shim
+            .ok("authorizer_builder_build", &[Ptr(builder), Ptr(biscuit)])
+            .handle();
+        let run_limits = shim.ok("authorizer_run_limits", &[U64(MAX_TIME)]).handle();
+        let outcome = shim.call("authorizer_authorize", &[Ptr(authorizer), Ptr(run_limits)]);
+        assert_eq!(outcome.is_ok(), expected, "{code}: {outcome:?}");
+        shim.ok("authorizer_drop", &[Ptr(authorizer)]).unit();
+    }
+    shim.ok("biscuit_drop", &[Ptr(biscuit)]).unit();
+    shim.ok("keypair_drop", &[Ptr(root)]).unit();
+}
+
+fn dispatch(shim: &mut Shim) {
+    let mut send = |request: Value| -> Value {
+        // the response can hold a private key, it is released with secret_string_free
+        let response = shim
+            .call_secret("dispatch", &[Str(&request.to_string())])
+            .unwrap()
+            .string();
+        serde_json::from_str(&response).unwrap()
+    };
+    let keypair = send(
+        json!({ "id": 1, "method": "keypair_new", "params": { "signature_algorithm": "ed25519" } }),
+    );
+    let keypair = &keypair["result"];
+    let dropped =
+        send(json!({ "id": 2, "method": "keypair_drop", "params": { "keypair": keypair } }));
+    assert_eq!(dropped, json!({ "id": 2, "result": null }));
+    let error =
+        send(json!({ "id": 3, "method": "keypair_drop", "params": { "keypair": keypair } }));
+    assert!(error["error"]["Dispatch"].is_string(), "{error}");
+
+    let keypair = send(
+        json!({ "id": 4, "method": "keypair_new", "params": { "signature_algorithm": "ed25519" } }),
+    );
+    let private_key = send(
+        json!({ "id": 5, "method": "keypair_private_key", "params": { "keypair": keypair["result"] } }),
+    );
+    let hex = send(
+        json!({ "id": 6, "method": "private_key_to_hex", "params": { "private_key": private_key["result"] } }),
+    );
+    assert!(
+        hex["result"]
+            .as_str()
+            .unwrap()
+            .starts_with("ed25519-private/"),
+        "{hex}"
+    );
+    send(
+        json!({ "id": 7, "method": "private_key_drop", "params": { "private_key": private_key["result"] } }),
+    );
+    send(json!({ "id": 8, "method": "keypair_drop", "params": { "keypair": keypair["result"] } }));
+}
+
+fn versions(shim: &mut Shim) {
+    assert_eq!(shim.ok("shim_version", &[]).string(), "0.1.0");
+    assert!(!shim.ok("biscuit_auth_version", &[]).string().is_empty());
+    // the manifest in abi/ describes this version
+    let manifest: Value =
+        serde_json::from_str(include_str!("../../abi/biscuit_wasm_shim.json")).unwrap();
+    assert_eq!(
+        shim.ok("abi_version", &[]).number(),
+        manifest["abi_version"]
+    );
+    let features: Value = serde_json::from_str(&shim.ok("shim_features", &[]).string()).unwrap();
+    assert_eq!(
+        features["features"],
+        json!(["print", "extern-funcs", "dispatch"])
+    );
+}
+
+const SCENARIOS: &[fn(&mut Shim)] = &[
+    keys,
+    builders,
+    tokens,
+    external_signature,
+    batch,
+    datalog,
+    extern_funcs,
+    dispatch,
+    versions,
+];
+
+#[test]
+fn every_export_is_called() {
+    let mut shim = Shim::load();
+    for scenario in SCENARIOS {
+        scenario(&mut shim);
+    }
+    let called = &shim.host().called;
+    let missing: Vec<_> = shim
+        .operations()
+        .into_iter()
+        .filter(|name| !called.contains(name))
+        .collect();
+    assert!(missing.is_empty(), "exports never called: {missing:?}");
+}
+
+/// Builds, serializes and verifies a token, releasing everything it allocates
+fn round(shim: &mut Shim) {
+    let (root, biscuit) = token(shim, ED25519);
+    let base64 = shim.ok("biscuit_to_base64", &[Ptr(biscuit)]).string();
+    let private_key = shim.ok("keypair_private_key", &[Ptr(root)]).handle();
+    let keypair = shim
+        .ok("keypair_from_private_key", &[Ptr(private_key)])
+        .handle();
+    let block = shim.ok("block_builder_new", &[]).handle();
+    shim.ok(
+        "block_builder_add_code",
+        &[Ptr(block), Str("check if true;")],
+    )
+    .unit();
+    let attenuated = shim
+        .ok("biscuit_append", &[Ptr(biscuit), Ptr(block)])
+        .handle();
+    assert_eq!(authorize(shim, "allow if true;", attenuated).unwrap(), 0);
+    shim.err(
+        "biscuit_verify_and_authorize",
+        &[
+            Str(&base64),
+            Str("ed25519/00"),
+            Str(""),
+            Str(""),
+            Str(LIMITS),
+        ],
+    );
+    for (name, handle) in [
+        ("biscuit_drop", attenuated),
+        ("biscuit_drop", biscuit),
+        ("keypair_drop", keypair),
+        ("private_key_drop", private_key),
+        ("keypair_drop", root),
+    ] {
+        shim.ok(name, &[Ptr(handle)]).unit();
+    }
+}
+
+/// Hosts allocating the 12 bytes return area themselves, without `get_return_area`
+#[test]
+fn host_allocated_return_area() {
+    let mut shim = Shim::load();
+    let area = shim.raw("malloc", &[Val::I32(16), Val::I32(4)])[0]
+        .i32()
+        .unwrap();
+    let area = area as usize;
+    // the 4 bytes after the area must be left untouched by the exports
+    shim.memory()[area + 12..area + 16].copy_from_slice(&[0xaa; 4]);
+    shim.raw("shim_version", &[Val::I32(area as i32)]);
+    let memory = shim.memory();
+    assert_eq!(memory[area + 12..area + 16], [0xaa; 4]);
+    let len = u32::from_le_bytes(memory[area + 4..area + 8].try_into().unwrap());
+    assert_eq!(len as usize, "0.1.0".len());
+    shim.raw("wasm_result_free", &[Val::I32(area as i32)]);
+    shim.raw("free", &[Val::I32(area as i32), Val::I32(16), Val::I32(4)]);
+}
+
+#[test]
+fn repeated_calls_do_not_leak() {
+    let mut shim = Shim::load();
+    // the first rounds grow the heap to its working size
+    for _ in 0..10 {
+        round(&mut shim);
+    }
+    let size = shim.memory_size();
+    for _ in 0..100 {
+        round(&mut shim);
+    }
+    assert_eq!(shim.memory_size(), size);
+}
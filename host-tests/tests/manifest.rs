@@ -0,0 +1,43 @@
+//! Checks the manifest in `abi/` against the exports of the module
+
+use host_tests::Shim;
+use serde_json::Value;
+use wasmi::core::ValType;
+
+/// Exports of the shim that are not declared with `wasm_export!`
+const NOT_IN_MANIFEST: &[&str] = &["wasm_result_free", "secret_string_free"];
+
+#[test]
+fn manifest_matches_the_module() {
+    let shim = Shim::load();
+    let manifest: Value = serde_json::from_str(include_str!("../../abi/biscuit_wasm_shim.json"))
+        .expect("the manifest is valid JSON");
+
+    let mut names = Vec::new();
+    for export in manifest["exports"].as_array().unwrap() {
+        let name = export["name"].as_str().unwrap();
+        let ty = shim
+            .func_type(name)
+            .unwrap_or_else(|| panic!("{name} is in the manifest but not exported"));
+
+        // the return area comes first, slices are lowered to a pointer and a length
+        let mut params = vec![ValType::I32];
+        for param in export["params"].as_array().unwrap() {
+            match param["type"].as_str().unwrap() {
+                "str" | "bytes" => params.extend([ValType::I32, ValType::I32]),
+                "u64" => params.push(ValType::I64),
+                _ => params.push(ValType::I32),
+            }
+        }
+        assert_eq!(ty.params(), params, "{name}");
+        assert!(ty.results().is_empty(), "{name}");
+        names.push(name.to_string());
+    }
+
+    for name in shim.operations() {
+        assert!(
+            names.contains(&name) || NOT_IN_MANIFEST.contains(&name.as_str()),
+            "{name} is exported but not in the manifest"
+        );
+    }
+}
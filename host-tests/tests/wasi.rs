@@ -0,0 +1,62 @@
+//! Runs datalog in the `wasm32-wasip1` module, whose clock comes from WASI `clock_time_get`
+
+use host_tests::{Arg::*, Kind, Shim, Target};
+
+const ED25519: u32 = 0;
+const MAX_TIME: u64 = 10_000;
+const LIMITS: &str = r#"{"max_time": 10000}"#;
+
+#[test]
+fn authorize_on_wasip1() {
+    let mut shim = Shim::load_target(Target::Wasip1);
+
+    let root = shim.ok("keypair_new", &[Ptr(ED25519)]).handle();
+    let builder = shim.ok("biscuit_builder_new", &[]).handle();
+    shim.ok(
+        "biscuit_builder_add_code",
+        &[Ptr(builder), Str(r#"right("file1", "read");"#)],
+    )
+    .unit();
+    let biscuit = shim
+        .ok(
+            "biscuit_builder_build_with_key_pair",
+            &[Ptr(builder), Ptr(root)],
+        )
+        .handle();
+
+    for (operation, expected) in [("read", Some(0)), ("write", None)] {
+        let builder = shim.ok("authorizer_builder_new", &[]).handle();
+        let code =
+            format!(r#"operation("{operation}"); allow if right("file1", $op), operation($op);"#);
+        shim.ok("authorizer_builder_add_code", &[Ptr(builder), Str(&code)])
+            .unit();
+        let authorizer = shim
+            .ok("authorizer_builder_build", &[Ptr(builder), Ptr(biscuit)])
+            .handle();
+        let run_limits = shim.ok("authorizer_run_limits", &[U64(MAX_TIME)]).handle();
+        let outcome = shim.call("authorizer_authorize", &[Ptr(authorizer), Ptr(run_limits)]);
+        match expected {
+            Some(policy) => assert_eq!(outcome.unwrap().number(), policy),
+            None => assert_eq!(outcome.unwrap_err().kind, Kind::ErrBiscuit),
+        }
+        shim.ok("authorizer_drop", &[Ptr(authorizer)]).unit();
+    }
+
+    let base64 = shim.ok("biscuit_to_base64", &[Ptr(biscuit)]).string();
+    let public_key = shim.ok("keypair_public_key", &[Ptr(root)]).handle();
+    let public_hex = shim.ok("public_key_to_hex", &[Ptr(public_key)]).string();
+    let policy = shim
+        .ok(
+            "biscuit_verify_and_authorize",
+            &[
+                Str(&base64),
+                Str(&public_hex),
+                Str(r#"allow if right("file1", "read");"#),
+                Str(""),
+                Str(LIMITS),
+            ],
+        )
+        .number();
+    assert_eq!(policy, 0);
+    assert!(shim.host().output.is_empty());
+}
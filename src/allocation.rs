@@ -2,7 +2,15 @@ use crate::wasm_result::WasmResult;
 use alloc::alloc;
 use std::mem;
 
-#[unsafe(no_mangle)]
+// `malloc` and `free` are only exported on wasm, on native targets they would
+// shadow the libc allocator. WASI targets link wasi-libc, which defines them
+// too: they are exported as `shim_malloc` and `shim_free` there
+#[cfg_attr(
+    all(target_arch = "wasm32", not(target_os = "wasi")),
+    unsafe(no_mangle)
+)]
+#[cfg_attr(target_os = "wasi", unsafe(export_name = "shim_malloc"))]
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 /// Allocates a new area of memory and returns a pointer to it.
 /// *Parameters*
 /// - `size`: allocated size in bytes
@@ -10,8 +18,14 @@ use std::mem;
 pub fn malloc(size: usize, align: usize) -> *mut u8 {
     unsafe { alloc::alloc(alloc::Layout::from_size_align_unchecked(size, align)) }
 }
-#[unsafe(no_mangle)]
+#[cfg_attr(
+    all(target_arch = "wasm32", not(target_os = "wasi")),
+    unsafe(no_mangle)
+)]
+#[cfg_attr(target_os = "wasi", unsafe(export_name = "shim_free"))]
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
 /// Frees a previously allocated area
+/// Bytes returned through a WasmResult must be released with `wasm_result_free` instead
 /// *Parameters*
 /// - `ptr`: allocated address
 /// - `size`: allocated size
@@ -24,6 +38,19 @@ pub fn free(ptr: *mut u8, size: usize, align: usize) {
 /// Allocates a WasmResult area and returns a pointer to it.
 /// Rust doesn't handle the allocated region, user is responsible for
 /// freeing after usage
+///
+/// The area is 12 bytes, `{ ptr, len, kind }` with 4 bytes fields.
 pub fn get_return_area() -> Box<WasmResult> {
     Box::new(unsafe { mem::zeroed() })
 }
+
+#[unsafe(no_mangle)]
+/// Frees the bytes owned by a WasmResult (string, byte buffer or error message),
+/// which are allocated with exactly `len` bytes.
+/// Opaque pointers returned in a WasmResult are not released, use the
+/// matching `*_drop` function for them.
+/// *Parameters*
+/// - `result`: the return area filled by a previous call
+pub fn wasm_result_free(result: &mut WasmResult) {
+    result.free();
+}
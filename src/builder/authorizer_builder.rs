@@ -1,4 +1,7 @@
-use crate::builder::in_place_apply;
+use crate::builder::json::{AuthorizerBuilderJson, BuilderContentJson, FromJsonError, ParsedItems};
+use crate::builder::lint::lint;
+use crate::builder::{in_place_apply, in_place_apply_no_return};
+use crate::snapshot::read_scopes;
 use crate::wasm_export;
 use crate::wasm_result::WasmResult;
 use biscuit_auth::{Authorizer, AuthorizerBuilder, Biscuit};
@@ -15,6 +18,38 @@ wasm_export!(
     }
 );
 
+// create an authorizer builder from a JSON description
+// Input:
+// json: {"facts": [...], "rules": [...], "checks": [...], "policies": [...], "scopes": [...]}, each element being a Datalog string,
+// scopes being "authority", "previous" or a public key, missing sections are empty
+// the output of authorizer_builder_to_json is accepted
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the authorizer builder allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// the JSON error is {"Json": message} for a malformed document,
+// or {"Items": [{"section": "policies", "index": 0, "error": ...}, ...]} listing every invalid element
+// data_len is the length of the error message
+wasm_export!(
+    fn authorizer_builder_from_json(json: &str) -> Result<Box<AuthorizerBuilder>, FromJsonError> {
+        let description: AuthorizerBuilderJson = serde_json::from_str(json)?;
+        let items = ParsedItems::parse(
+            &description.facts,
+            &description.rules,
+            &description.checks,
+            &description.policies,
+            &description.scopes,
+        )?;
+        Ok(Box::new(items.into_authorizer_builder()?))
+    }
+);
+
 // drop the authorizer builder and free the memory allocated for it
 // Input:
 // builder is a pointer to the authorizer builder allocated in the wasm memory
@@ -75,3 +110,110 @@ wasm_export!(
         in_place_apply(builder, |builder| builder.code(code))
     }
 );
+
+// merge the facts, rules, checks, policies and scopes of an authorizer builder into another one
+// the limits of the destination builder are kept
+// Input:
+// builder is a pointer to the destination authorizer builder allocated in the wasm memory
+// other is a pointer to the source authorizer builder allocated in the wasm memory, consumed by the call
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+// the destination builder is left untouched on error
+wasm_export!(
+    fn authorizer_builder_merge(
+        builder: &mut AuthorizerBuilder,
+        other: Box<AuthorizerBuilder>,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        // `AuthorizerBuilder::merge` drops the scopes of the source, they are read
+        // from its snapshot and added back
+        let world = other.snapshot()?.world;
+        let scopes = read_scopes(&world.authorizer_block.scope, &world.public_keys)?;
+        in_place_apply_no_return(builder, |builder| {
+            scopes
+                .into_iter()
+                .fold(builder.merge(*other), |builder, scope| builder.scope(scope))
+        });
+        Ok(())
+    }
+);
+
+// get the authorizer builder as a string
+// Input:
+// builder: the authorizer builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// data is a pointer to the string allocated in the wasm memory
+// data_len is the length of the string in bytes
+// kind is Ok because the function never fails
+wasm_export!(
+    fn authorizer_builder_to_string(builder: &AuthorizerBuilder) -> String {
+        builder.to_string()
+    }
+);
+
+// get the content of the authorizer builder as JSON, for debugging
+// Input:
+// builder: the authorizer builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the JSON string allocated in the wasm memory:
+// { "facts": [..], "rules": [..], "checks": [..], "policies": [..], "scopes": [..] }
+// every element is printed as datalog, the document can be loaded with authorizer_builder_from_json
+// data_len is the length of the JSON string in bytes
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn authorizer_builder_to_json(
+        builder: &AuthorizerBuilder,
+    ) -> Result<String, biscuit_auth::error::Format> {
+        BuilderContentJson::from_authorizer_builder(builder)?.to_json()
+    }
+);
+
+// look for likely mistakes in the authorizer builder: no allow policy, policies that
+// can never be reached after an unconditional one like `allow if true`
+// Input:
+// builder: the authorizer builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the list of warnings allocated in the wasm memory, as JSON:
+// [{ "kind": "no_allow_policy" | "unreachable_policy", "message": "..",
+//    "section": "policies" | null, "index": 0 | null,
+//    "start": {"line": 1, "column": 1, "offset": 0} | null, "end": {..} | null }]
+// section and index locate the element as listed by authorizer_builder_to_json, start and end
+// in the code returned by authorizer_builder_to_string, as the spans of datalog_validate;
+// they are null when the warning is about the whole authorizer,
+// the list is empty when nothing was found
+// data_len is the length of the JSON string in bytes
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn authorizer_builder_lint(
+        builder: &AuthorizerBuilder,
+    ) -> Result<String, biscuit_auth::error::Token> {
+        serde_json::to_string(&lint(builder)?).map_err(|e| {
+            biscuit_auth::error::Format::SerializationError(format!("serialization error: {e:?}"))
+                .into()
+        })
+    }
+);
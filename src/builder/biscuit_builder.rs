@@ -1,8 +1,17 @@
-use crate::builder::{in_place_apply, in_place_apply_no_return};
+use crate::builder::json::{BiscuitBuilderJson, BuilderContentJson, FromJsonError, ParsedItems};
+use crate::builder::{
+    in_place_apply, in_place_apply_no_return, parse_check, parse_fact, parse_rule, unix_date,
+};
+use crate::snapshot::read_scopes;
 use crate::wasm_result::WasmResult;
 use crate::{make_rng, wasm_export};
+use biscuit_auth::builder::{BlockBuilder, Scope};
+use biscuit_auth::builder_ext::BuilderExt;
 use biscuit_auth::datalog::SymbolTable;
-use biscuit_auth::{Biscuit, BiscuitBuilder, KeyPair, PrivateKey};
+use biscuit_auth::error::Format;
+use biscuit_auth::format::schema;
+use biscuit_auth::{Algorithm, Biscuit, BiscuitBuilder, KeyPair, PrivateKey, PublicKey};
+use prost::Message;
 
 // create a new biscuit builder
 // Output:
@@ -16,6 +25,38 @@ wasm_export!(
     }
 );
 
+// create a biscuit builder from a JSON description
+// Input:
+// json: {"facts": [...], "rules": [...], "checks": [...], "scopes": [...]}, each element being a Datalog string,
+// scopes being "authority", "previous" or a public key, missing sections are empty
+// the output of biscuit_builder_to_json is accepted
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the biscuit builder allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// the JSON error is {"Json": message} for a malformed document,
+// or {"Items": [{"section": "facts", "index": 0, "error": ...}, ...]} listing every invalid element
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_from_json(json: &str) -> Result<Box<BiscuitBuilder>, FromJsonError> {
+        let description: BiscuitBuilderJson = serde_json::from_str(json)?;
+        let items = ParsedItems::parse(
+            &description.facts,
+            &description.rules,
+            &description.checks,
+            &[],
+            &description.scopes,
+        )?;
+        Ok(Box::new(items.into_biscuit_builder()?))
+    }
+);
+
 // drop the biscuit builder
 // Output:
 // returnArea { data, data_len=0, kind=Ok }
@@ -107,6 +148,281 @@ wasm_export!(
     }
 );
 
+// add a single fact to the biscuit builder
+// the fact is parsed and validated before the builder is modified, so the builder
+// stays usable if it is invalid
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// fact: the fact to add, e.g. `user("alice")`
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_add_fact(
+        builder: &mut BiscuitBuilder,
+        fact: &str,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        let fact = parse_fact(fact)?;
+        in_place_apply(builder, |builder| builder.fact(fact))
+    }
+);
+
+// add a single rule to the biscuit builder
+// the rule is parsed and validated before the builder is modified, so the builder
+// stays usable if it is invalid
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// rule: the rule to add, e.g. `can_read($file) <- right($file, "read")`
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_add_rule(
+        builder: &mut BiscuitBuilder,
+        rule: &str,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        let rule = parse_rule(rule)?;
+        in_place_apply(builder, |builder| builder.rule(rule))
+    }
+);
+
+// add a single check to the biscuit builder
+// the check is parsed and validated before the builder is modified, so the builder
+// stays usable if it is invalid
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// check: the check to add, e.g. `check if time($time), $time < 2030-01-01T00:00:00Z`
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_add_check(
+        builder: &mut BiscuitBuilder,
+        check: &str,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        let check = parse_check(check)?;
+        in_place_apply(builder, |builder| builder.check(check))
+    }
+);
+
+// add the `authority` scope to the authority block: trust the authority block and the authorizer
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_add_scope_authority(builder: &mut BiscuitBuilder) {
+        in_place_apply_no_return(builder, |builder| builder.scope(Scope::Authority));
+    }
+);
+
+// add the `previous` scope to the authority block: trust the current and previous blocks
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_add_scope_previous(builder: &mut BiscuitBuilder) {
+        in_place_apply_no_return(builder, |builder| builder.scope(Scope::Previous));
+    }
+);
+
+// add a public key scope to the authority block: trust the blocks signed by this key
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// public_key: the public key pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_add_scope_public_key(builder: &mut BiscuitBuilder, public_key: &PublicKey) {
+        in_place_apply_no_return(builder, |builder| {
+            builder.scope(Scope::PublicKey(*public_key))
+        });
+    }
+);
+
+// add a check to the biscuit builder: check if time($time), $time <= <date>
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// unix_seconds: the expiration date in seconds since the Unix epoch
+//
+// Output:
+// returnArea { data=0, data_len=0, kind }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit
+// the date is too large to be represented, the builder is left as it was
+// data is the pointer to the JSON error message allocated in the wasm memory
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_check_expiration_date(
+        builder: &mut BiscuitBuilder,
+        unix_seconds: u64,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        let date = unix_date(unix_seconds)?;
+        in_place_apply_no_return(builder, |builder| builder.check_expiration_date(date));
+        Ok(())
+    }
+);
+
+// add a check to the biscuit builder: check if resource(<name>)
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// name: the name of the resource
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_check_resource(builder: &mut BiscuitBuilder, name: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_resource(name));
+    }
+);
+
+// add a check to the biscuit builder: check if resource($resource), $resource.starts_with(<prefix>)
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// prefix: the prefix of the resource
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_check_resource_prefix(builder: &mut BiscuitBuilder, prefix: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_resource_prefix(prefix));
+    }
+);
+
+// add a check to the biscuit builder: check if resource($resource), $resource.ends_with(<suffix>)
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// suffix: the suffix of the resource
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_check_resource_suffix(builder: &mut BiscuitBuilder, suffix: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_resource_suffix(suffix));
+    }
+);
+
+// add a check to the biscuit builder: check if operation(<operation>)
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// operation: the name of the operation
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_check_operation(builder: &mut BiscuitBuilder, operation: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_operation(operation));
+    }
+);
+
+// merge the facts, rules, checks and scopes of a biscuit builder into another one
+// the context and root key id of the source builder are not merged
+// Input:
+// builder: the destination biscuit builder pointer to the allocated wasm memory
+// other: the source biscuit builder pointer to the allocated wasm memory, consumed by the call
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+// the destination builder is left untouched on error
+wasm_export!(
+    fn biscuit_builder_merge(
+        builder: &mut BiscuitBuilder,
+        other: Box<BiscuitBuilder>,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        // reading the scopes is the only step that can fail, it runs before the
+        // destination is modified
+        let scopes = authority_scopes(&other)?;
+        let (facts, rules, checks) = other.dump();
+        // the elements were validated when they were added to the source builder
+        let block = BlockBuilder {
+            facts,
+            rules,
+            checks,
+            ..BlockBuilder::default()
+        };
+        in_place_apply_no_return(builder, |builder| {
+            scopes
+                .into_iter()
+                .fold(builder.merge(block), |builder, scope| builder.scope(scope))
+        });
+        Ok(())
+    }
+);
+
+/// Reads the scopes of a biscuit builder
+///
+/// biscuit-auth does not expose them, they are read back from an authority block
+/// signed with a throwaway key.
+fn authority_scopes(builder: &BiscuitBuilder) -> Result<Vec<Scope>, biscuit_auth::error::Token> {
+    let mut rng = make_rng();
+    let throwaway = KeyPair::new_with_rng(Algorithm::Ed25519, &mut rng);
+    let biscuit = builder
+        .clone()
+        .build_with_rng(&throwaway, SymbolTable::default(), &mut rng)?;
+    let authority = schema::Block::decode(&*biscuit.container().to_proto().authority.block)
+        .map_err(|e| Format::DeserializationError(format!("deserialization error: {e:?}")))?;
+    Ok(read_scopes(&authority.scope, &authority.public_keys)?)
+}
+
 // set the root key id
 // Input:
 // builder: the biscuit builder pointer to the allocated wasm memory
@@ -128,6 +444,22 @@ wasm_export!(
     }
 );
 
+// set the context of the authority block, a free form string (e.g. a request id)
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+// context: the context string
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_builder_set_context(builder: &mut BiscuitBuilder, context: &str) {
+        in_place_apply_no_return(builder, |builder| builder.context(context.to_string()));
+    }
+);
+
 // get the biscuit builder as a string
 // Input:
 // builder: the biscuit builder pointer to the allocated wasm memory
@@ -143,3 +475,28 @@ wasm_export!(
         builder.to_string()
     }
 );
+
+// get the content of the biscuit builder as JSON, for debugging
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the JSON string allocated in the wasm memory:
+// { "facts": [..], "rules": [..], "checks": [..] }
+// every element is printed as datalog, the document can be loaded with biscuit_builder_from_json
+// the scopes of the builder are not listed, biscuit-auth does not expose them
+// data_len is the length of the JSON string in bytes
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_to_json(
+        builder: &BiscuitBuilder,
+    ) -> Result<String, biscuit_auth::error::Format> {
+        BuilderContentJson::from_biscuit_builder(builder).to_json()
+    }
+);
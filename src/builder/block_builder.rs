@@ -0,0 +1,176 @@
+use crate::builder::{in_place_apply, in_place_apply_no_return, unix_date};
+use crate::wasm_export;
+use crate::wasm_result::WasmResult;
+use biscuit_auth::BlockBuilder;
+use biscuit_auth::builder_ext::BuilderExt;
+
+// create a new block builder, used to attenuate a biscuit or to create a third party block
+// Output:
+// returnArea { data, data_len=0, kind=Ok }
+// data is a pointer to the block builder allocated in the wasm memory
+// data_len is 0 because of the opaque type
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_new() -> Box<BlockBuilder> {
+        Box::new(BlockBuilder::new())
+    }
+);
+
+// drop the block builder
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// Output:
+// returnArea { data, data_len=0, kind=Ok }
+// data is 0
+// data_len is 0
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_drop(builder: Box<BlockBuilder>) {
+        drop(builder);
+    }
+);
+
+// add a code to the block builder
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// code {ptr, len}: the code to add
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn block_builder_add_code(
+        builder: &mut BlockBuilder,
+        code: &str,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        in_place_apply(builder, |builder| builder.code(code))
+    }
+);
+
+// add a check to the block builder: check if time($time), $time <= <date>
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// unix_seconds: the expiration date in seconds since the Unix epoch
+//
+// Output:
+// returnArea { data=0, data_len=0, kind }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit
+// the date is too large to be represented, the builder is left as it was
+// data is the pointer to the JSON error message allocated in the wasm memory
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn block_builder_check_expiration_date(
+        builder: &mut BlockBuilder,
+        unix_seconds: u64,
+    ) -> Result<(), biscuit_auth::error::Token> {
+        let date = unix_date(unix_seconds)?;
+        in_place_apply_no_return(builder, |builder| builder.check_expiration_date(date));
+        Ok(())
+    }
+);
+
+// add a check to the block builder: check if resource(<name>)
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// name: the name of the resource
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_check_resource(builder: &mut BlockBuilder, name: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_resource(name));
+    }
+);
+
+// add a check to the block builder: check if resource($resource), $resource.starts_with(<prefix>)
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// prefix: the prefix of the resource
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_check_resource_prefix(builder: &mut BlockBuilder, prefix: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_resource_prefix(prefix));
+    }
+);
+
+// add a check to the block builder: check if resource($resource), $resource.ends_with(<suffix>)
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// suffix: the suffix of the resource
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_check_resource_suffix(builder: &mut BlockBuilder, suffix: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_resource_suffix(suffix));
+    }
+);
+
+// add a check to the block builder: check if operation(<operation>)
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// operation: the name of the operation
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_check_operation(builder: &mut BlockBuilder, operation: &str) {
+        in_place_apply_no_return(builder, |builder| builder.check_operation(operation));
+    }
+);
+
+// set the context of the block, a free form string (e.g. a request id)
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+// context: the context string
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_set_context(builder: &mut BlockBuilder, context: &str) {
+        in_place_apply_no_return(builder, |builder| builder.context(context.to_string()));
+    }
+);
+
+// get the block builder as a string
+// Input:
+// builder: the block builder pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// data is a pointer to the string allocated in the wasm memory
+// data_len is the length of the string in bytes
+// kind is Ok because the function never fails
+wasm_export!(
+    fn block_builder_to_string(builder: &BlockBuilder) -> String {
+        builder.to_string()
+    }
+);
@@ -0,0 +1,64 @@
+use crate::term_json::{term_from_json, term_to_json};
+use crate::wasm_export;
+use crate::wasm_result::WasmResult;
+use biscuit_auth::AuthorizerBuilder;
+use biscuit_auth::builder::Term;
+use biscuit_auth::datalog::ExternFunc;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Calls the host implementation of the extern function `name`
+fn call_host(name: &str, left: Term, right: Option<Term>) -> Result<Term, String> {
+    let mut args = vec![term_to_json(&left)?];
+    if let Some(right) = right {
+        args.push(term_to_json(&right)?);
+    }
+    let args = Value::Array(args).to_string();
+
+    let mut result = WasmResult::new();
+    unsafe {
+        crate::extern_func_call(
+            name.as_ptr(),
+            name.len(),
+            args.as_ptr(),
+            args.len(),
+            &mut result,
+        )
+    };
+    match result.into_host_bytes() {
+        Ok(bytes) => {
+            let value = serde_json::from_slice(&bytes)
+                .map_err(|e| format!("invalid term returned by `{name}`: {e}"))?;
+            term_from_json(value)
+        }
+        Err(message) => Err(String::from_utf8_lossy(&message).into_owned()),
+    }
+}
+
+// register an extern function, called in expressions with `.extern::<name>(..)`
+// its implementation is the host import
+// extern_func_call(name_ptr, name_len, args_ptr, args_len, result)
+// which receives the name of the function and its arguments as a JSON array of one or two terms,
+// and fills the result return area { data, data_len, kind } with a buffer allocated with malloc(data_len, 1):
+// kind = Ok and the returned term in JSON, or kind = ErrBiscuit and an error message
+// Input:
+// builder: the authorizer builder pointer to the allocated wasm memory
+// name {ptr, len}: the name of the function
+//
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn authorizer_builder_register_extern_func(builder: &mut AuthorizerBuilder, name: &str) {
+        let name = name.to_string();
+        let func = ExternFunc::new(Arc::new({
+            let name = name.clone();
+            move |left, right| call_host(&name, left, right)
+        }));
+        super::in_place_apply_no_return(builder, |builder| {
+            builder.register_extern_func(name, func)
+        });
+    }
+);
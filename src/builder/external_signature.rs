@@ -0,0 +1,152 @@
+use crate::wasm_result::WasmResult;
+use crate::{make_rng, wasm_export};
+use biscuit_auth::datalog::SymbolTable;
+use biscuit_auth::error::{Format, Token};
+use biscuit_auth::format::schema;
+use biscuit_auth::{Biscuit, BiscuitBuilder, KeyPair, PublicKey};
+use prost::Message;
+
+/// Biscuit whose authority block is waiting for the signature of the root key
+///
+/// The root private key never enters the wasm memory: the host signs the
+/// payload with an external signer (HSM, KMS...) and hands the signature back.
+pub struct UnsignedBiscuit {
+    /// serialized token, the authority signature is a placeholder
+    token: schema::Biscuit,
+    /// bytes the root key must sign
+    payload: Vec<u8>,
+    /// root public key, used to verify the external signature
+    root_public_key: PublicKey,
+}
+
+/// Rebuilds the authority block signature payload, as biscuit-auth does when verifying it
+///
+/// biscuit-auth does not export its payload functions, this copy is checked against
+/// the signatures it computes by `tests::external_signature`
+pub(crate) fn authority_signature_payload(block: &schema::SignedBlock) -> Vec<u8> {
+    match block.version.unwrap_or(0) {
+        0 => {
+            let mut payload = block.block.clone();
+            payload.extend(block.next_key.algorithm.to_le_bytes());
+            payload.extend(&block.next_key.key);
+            payload
+        }
+        version => {
+            let mut payload = b"\0BLOCK\0\0VERSION\0".to_vec();
+            payload.extend(version.to_le_bytes());
+            payload.extend(b"\0PAYLOAD\0");
+            payload.extend(&block.block);
+            payload.extend(b"\0ALGORITHM\0");
+            payload.extend(block.next_key.algorithm.to_le_bytes());
+            payload.extend(b"\0NEXTKEY\0");
+            payload.extend(&block.next_key.key);
+            payload
+        }
+    }
+}
+
+// prepare a biscuit to be signed by an external signer
+// Input:
+// builder: the biscuit builder pointer to the allocated wasm memory, consumed by the call
+// root_public_key: the public part of the external root key pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the unsigned biscuit allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn biscuit_builder_prepare_external_signature(
+        builder: Box<BiscuitBuilder>,
+        root_public_key: &PublicKey,
+    ) -> Result<Box<UnsignedBiscuit>, Token> {
+        // the authority block is first signed with a throwaway key of the same algorithm
+        // as the root key, so that the signature version matches the one of the real root key
+        let mut rng = make_rng();
+        let placeholder_root = KeyPair::new_with_rng(root_public_key.algorithm().into(), &mut rng);
+        let biscuit =
+            builder.build_with_rng(&placeholder_root, SymbolTable::default(), &mut rng)?;
+
+        let token = biscuit.container().to_proto();
+        let payload = authority_signature_payload(&token.authority);
+
+        Ok(Box::new(UnsignedBiscuit {
+            token,
+            payload,
+            root_public_key: *root_public_key,
+        }))
+    }
+);
+
+// get the bytes the root key must sign
+// Input:
+// unsigned_biscuit: the unsigned biscuit pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind=Ok }
+// data is the pointer to the payload allocated in the wasm memory
+// data_len is the length of the payload in bytes
+// kind is Ok because the function never fails
+wasm_export!(
+    fn unsigned_biscuit_signature_payload(unsigned_biscuit: &UnsignedBiscuit) -> Vec<u8> {
+        unsigned_biscuit.payload.clone()
+    }
+);
+
+// complete the biscuit with the signature computed by the external signer
+// Input:
+// unsigned_biscuit: the unsigned biscuit pointer to the allocated wasm memory, consumed by the call
+// signature {ptr, len}: the signature of the payload by the root key
+// (64 bytes for Ed25519, DER encoded ECDSA signature for Secp256r1)
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the biscuit allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn unsigned_biscuit_finish(
+        unsigned_biscuit: Box<UnsignedBiscuit>,
+        signature: &[u8],
+    ) -> Result<Box<Biscuit>, Token> {
+        let UnsignedBiscuit {
+            mut token,
+            root_public_key,
+            ..
+        } = *unsigned_biscuit;
+        token.authority.signature = signature.to_vec();
+
+        let mut data = Vec::new();
+        token
+            .encode(&mut data)
+            .map_err(|e| Format::SerializationError(format!("serialization error: {e:?}")))?;
+
+        // parsing verifies the external signature against the root public key
+        Ok(Box::new(Biscuit::from(data, root_public_key)?))
+    }
+);
+
+// drop the unsigned biscuit
+// Input:
+// unsigned_biscuit: the unsigned biscuit pointer to the allocated wasm memory
+//
+// Output:
+// returnArea { data, data_len=0, kind=Ok }
+// data is 0
+// data_len is 0
+// kind is Ok because the function never fails
+wasm_export!(
+    fn unsigned_biscuit_drop(unsigned_biscuit: Box<UnsignedBiscuit>) {
+        drop(unsigned_biscuit);
+    }
+);
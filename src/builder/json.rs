@@ -0,0 +1,214 @@
+use crate::builder::{parse_check, parse_fact, parse_policy, parse_rule, parse_scope};
+use crate::snapshot::{SnapshotPrinter, print_scopes};
+use biscuit_auth::builder::{Check, Fact, Policy, Rule, Scope};
+use biscuit_auth::error::{Format, Token};
+use biscuit_auth::{AuthorizerBuilder, BiscuitBuilder};
+use serde::{Deserialize, Serialize};
+
+// the descriptions accepted by `*_builder_from_json` have the sections written by
+// `*_builder_to_json`, so a dump can be loaded back
+
+/// JSON description of a biscuit builder
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct BiscuitBuilderJson {
+    #[serde(default)]
+    pub facts: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<String>,
+    #[serde(default)]
+    pub checks: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// JSON description of an authorizer builder
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct AuthorizerBuilderJson {
+    #[serde(default)]
+    pub facts: Vec<String>,
+    #[serde(default)]
+    pub rules: Vec<String>,
+    #[serde(default)]
+    pub checks: Vec<String>,
+    #[serde(default)]
+    pub policies: Vec<String>,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+/// Error returned when loading a builder from JSON
+#[derive(Serialize)]
+pub enum FromJsonError {
+    /// The document is not valid JSON or does not have the expected fields
+    Json(String),
+    /// Some elements could not be parsed, all of them are reported
+    Items(Vec<ItemError>),
+    /// The elements are valid but could not be added to the builder
+    Builder(Token),
+}
+
+/// Location and cause of an invalid element
+#[derive(Serialize)]
+pub struct ItemError {
+    /// `facts`, `rules`, `checks`, `policies` or `scopes`
+    pub section: &'static str,
+    /// index of the element in its section
+    pub index: usize,
+    pub error: Token,
+}
+
+impl From<Token> for FromJsonError {
+    fn from(error: Token) -> Self {
+        FromJsonError::Builder(error)
+    }
+}
+
+impl From<serde_json::Error> for FromJsonError {
+    fn from(error: serde_json::Error) -> Self {
+        FromJsonError::Json(error.to_string())
+    }
+}
+
+/// Parses every element of a section, recording the location of the invalid ones
+fn parse_section<T>(
+    section: &'static str,
+    items: &[String],
+    parse: impl Fn(&str) -> Result<T, Token>,
+    errors: &mut Vec<ItemError>,
+) -> Vec<T> {
+    items
+        .iter()
+        .enumerate()
+        .filter_map(|(index, item)| match parse(item) {
+            Ok(parsed) => Some(parsed),
+            Err(error) => {
+                errors.push(ItemError {
+                    section,
+                    index,
+                    error,
+                });
+                None
+            }
+        })
+        .collect()
+}
+
+/// Elements of a builder, parsed and validated
+pub(crate) struct ParsedItems {
+    pub facts: Vec<Fact>,
+    pub rules: Vec<Rule>,
+    pub checks: Vec<Check>,
+    pub policies: Vec<Policy>,
+    pub scopes: Vec<Scope>,
+}
+
+impl ParsedItems {
+    pub(crate) fn parse(
+        facts: &[String],
+        rules: &[String],
+        checks: &[String],
+        policies: &[String],
+        scopes: &[String],
+    ) -> Result<Self, FromJsonError> {
+        let mut errors = Vec::new();
+        let parsed = ParsedItems {
+            facts: parse_section("facts", facts, parse_fact, &mut errors),
+            rules: parse_section("rules", rules, parse_rule, &mut errors),
+            checks: parse_section("checks", checks, parse_check, &mut errors),
+            policies: parse_section("policies", policies, parse_policy, &mut errors),
+            scopes: parse_section("scopes", scopes, parse_scope, &mut errors),
+        };
+        if errors.is_empty() {
+            Ok(parsed)
+        } else {
+            Err(FromJsonError::Items(errors))
+        }
+    }
+
+    pub(crate) fn into_biscuit_builder(self) -> Result<BiscuitBuilder, Token> {
+        let mut builder = BiscuitBuilder::new();
+        for fact in self.facts {
+            builder = builder.fact(fact)?;
+        }
+        for rule in self.rules {
+            builder = builder.rule(rule)?;
+        }
+        for check in self.checks {
+            builder = builder.check(check)?;
+        }
+        for scope in self.scopes {
+            builder = builder.scope(scope);
+        }
+        Ok(builder)
+    }
+
+    pub(crate) fn into_authorizer_builder(self) -> Result<AuthorizerBuilder, Token> {
+        let mut builder = AuthorizerBuilder::new();
+        for fact in self.facts {
+            builder = builder.fact(fact)?;
+        }
+        for rule in self.rules {
+            builder = builder.rule(rule)?;
+        }
+        for check in self.checks {
+            builder = builder.check(check)?;
+        }
+        for policy in self.policies {
+            builder = builder.policy(policy)?;
+        }
+        for scope in self.scopes {
+            builder = builder.scope(scope);
+        }
+        Ok(builder)
+    }
+}
+
+/// Content of a builder, every element printed as datalog
+#[derive(Serialize)]
+pub(crate) struct BuilderContentJson {
+    pub facts: Vec<String>,
+    pub rules: Vec<String>,
+    pub checks: Vec<String>,
+    /// absent for a biscuit builder
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub policies: Option<Vec<String>>,
+    /// absent for a biscuit builder, whose scopes are not exposed by biscuit-auth
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scopes: Option<Vec<String>>,
+}
+
+impl BuilderContentJson {
+    pub(crate) fn from_biscuit_builder(builder: &BiscuitBuilder) -> Self {
+        let (facts, rules, checks) = builder.dump();
+
+        BuilderContentJson {
+            facts: facts.iter().map(ToString::to_string).collect(),
+            rules: rules.iter().map(ToString::to_string).collect(),
+            checks: checks.iter().map(ToString::to_string).collect(),
+            policies: None,
+            scopes: None,
+        }
+    }
+
+    pub(crate) fn from_authorizer_builder(builder: &AuthorizerBuilder) -> Result<Self, Format> {
+        let world = builder.snapshot()?.world;
+        let printer = SnapshotPrinter::new(&world)?;
+        let block = &world.authorizer_block;
+        let version = block.version.unwrap_or(0);
+
+        Ok(BuilderContentJson {
+            facts: printer.facts(&block.facts)?,
+            rules: printer.rules(&block.rules, version)?,
+            checks: printer.checks(&block.checks, version)?,
+            policies: Some(printer.policies(&world.authorizer_policies, version)?),
+            scopes: Some(print_scopes(&block.scope, &world.public_keys)?),
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String, Format> {
+        serde_json::to_string(self)
+            .map_err(|e| Format::SerializationError(format!("serialization error: {e:?}")))
+    }
+}
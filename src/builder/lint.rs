@@ -0,0 +1,104 @@
+use crate::datalog::{Position, span};
+use biscuit_auth::AuthorizerBuilder;
+use biscuit_auth::builder::{Op, Policy, PolicyKind, Term};
+use biscuit_auth::error::Token;
+use biscuit_parser::error::LanguageError;
+use biscuit_parser::parser::parse_source;
+use serde::Serialize;
+
+/// Likely mistake found in an authorizer
+#[derive(Serialize)]
+pub(crate) struct LintWarning {
+    pub kind: LintKind,
+    pub message: String,
+    /// `policies`, absent when the warning is about the whole authorizer
+    pub section: Option<&'static str>,
+    /// index of the element in its section, as listed by `authorizer_builder_to_json`
+    pub index: Option<usize>,
+    /// span of the element in the code printed by `authorizer_builder_to_string`
+    pub start: Option<Position>,
+    pub end: Option<Position>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum LintKind {
+    /// every request will be denied
+    NoAllowPolicy,
+    /// a previous policy always matches
+    UnreachablePolicy,
+}
+
+/// A policy matches whatever the request when one of its queries has no
+/// predicate and only `true` as expressions, as in `allow if true`
+fn is_unconditional(policy: &Policy) -> bool {
+    policy.queries.iter().any(|query| {
+        query.body.is_empty()
+            && query
+                .expressions
+                .iter()
+                .all(|expression| matches!(expression.ops[..], [Op::Value(Term::Bool(true))]))
+    })
+}
+
+fn policy_kind(policy: &Policy) -> &'static str {
+    match policy.kind {
+        PolicyKind::Allow => "allow",
+        PolicyKind::Deny => "deny",
+    }
+}
+
+/// Policies with their source, `code` is the printed authorizer they are parsed from
+fn lint_policies(code: &str, policies: &[(&str, Policy)], warnings: &mut Vec<LintWarning>) {
+    if !policies
+        .iter()
+        .any(|(_, policy)| matches!(policy.kind, PolicyKind::Allow))
+    {
+        warnings.push(LintWarning {
+            kind: LintKind::NoAllowPolicy,
+            message: "the authorizer has no allow policy, every request will be denied".to_string(),
+            section: None,
+            index: None,
+            start: None,
+            end: None,
+        });
+    }
+
+    if let Some(first) = policies
+        .iter()
+        .position(|(_, policy)| is_unconditional(policy))
+    {
+        for (index, (part, _)) in policies.iter().enumerate().skip(first + 1) {
+            let (start, end) = span(code, part);
+            warnings.push(LintWarning {
+                kind: LintKind::UnreachablePolicy,
+                message: format!(
+                    "unreachable policy, the {} policy {first} always matches before it",
+                    policy_kind(&policies[first].1)
+                ),
+                section: Some("policies"),
+                index: Some(index),
+                start: Some(start),
+                end: Some(end),
+            });
+        }
+    }
+}
+
+// rules whose head uses variables their body does not bind are not linted: the
+// parser and the builders reject them before they reach an authorizer builder
+
+pub(crate) fn lint(builder: &AuthorizerBuilder) -> Result<Vec<LintWarning>, Token> {
+    // the builder does not keep the code it was built from, positions point to its printed form
+    let code = builder.to_string();
+    let source = parse_source(&code).map_err(LanguageError::from)?;
+    let policies: Vec<_> = source
+        .policies
+        .into_iter()
+        .map(|(part, policy)| (part, Policy::from(policy)))
+        .collect();
+
+    let mut warnings = Vec::new();
+    lint_policies(&code, &policies, &mut warnings);
+    Ok(warnings)
+}
@@ -1,5 +1,16 @@
-mod authorizer_builder;
-mod biscuit_builder;
+pub(crate) mod authorizer_builder;
+pub(crate) mod biscuit_builder;
+pub(crate) mod block_builder;
+#[cfg(feature = "extern-funcs")]
+pub(crate) mod extern_func;
+pub(crate) mod external_signature;
+mod json;
+mod lint;
+
+use biscuit_auth::PublicKey;
+use biscuit_auth::builder::{Check, Fact, Policy, Rule, Scope};
+use biscuit_auth::error::Token;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 fn in_place_apply<B, E, F>(builder: &mut B, f: F) -> Result<(), E>
 where
@@ -20,3 +31,50 @@ where
     let result = f(local);
     unsafe { core::ptr::write(builder, result) };
 }
+
+/// Converts a date received from the host, which can be past what `SystemTime` represents
+///
+/// Dates above `i64::MAX` seconds overflow the `SystemTime` of native targets but not the
+/// one of wasm32, they are rejected on every target so that both builds behave the same
+pub(crate) fn unix_date(unix_seconds: u64) -> Result<SystemTime, Token> {
+    i64::try_from(unix_seconds)
+        .ok()
+        .and_then(|_| UNIX_EPOCH.checked_add(Duration::from_secs(unix_seconds)))
+        .ok_or_else(|| Token::ConversionError(format!("invalid date: {unix_seconds}")))
+}
+
+// parse and validate a single element before adding it to a builder,
+// so that an invalid element leaves the builder untouched
+
+pub(crate) fn parse_fact(source: &str) -> Result<Fact, Token> {
+    let fact = Fact::try_from(source)?;
+    fact.validate()?;
+    Ok(fact)
+}
+
+pub(crate) fn parse_rule(source: &str) -> Result<Rule, Token> {
+    let rule = Rule::try_from(source)?;
+    rule.validate_parameters()?;
+    Ok(rule)
+}
+
+pub(crate) fn parse_check(source: &str) -> Result<Check, Token> {
+    let check = Check::try_from(source)?;
+    check.validate_parameters()?;
+    Ok(check)
+}
+
+pub(crate) fn parse_policy(source: &str) -> Result<Policy, Token> {
+    let policy = Policy::try_from(source)?;
+    policy.validate_parameters()?;
+    Ok(policy)
+}
+
+/// Parses a scope as printed by biscuit-auth: `authority`, `previous` or a public key
+pub(crate) fn parse_scope(source: &str) -> Result<Scope, Token> {
+    Ok(match source {
+        "authority" => Scope::Authority,
+        "previous" => Scope::Previous,
+        public_key => Scope::PublicKey(public_key.parse::<PublicKey>()?),
+    })
+}
@@ -0,0 +1,692 @@
+//! Component model interface described by `wit/shim.wit`
+//!
+//! The resources wrap the values handed out by the `wasm_export!` functions and
+//! every method calls the matching export, so both interfaces share one implementation.
+
+use crate::builder::authorizer_builder::*;
+use crate::builder::biscuit_builder::*;
+use crate::builder::block_builder::*;
+use crate::builder::external_signature::*;
+use crate::crypto;
+use crate::crypto::keypair::*;
+use crate::crypto::private_key::*;
+use crate::crypto::public_key::*;
+use crate::datalog::{self, datalog_format, datalog_validate};
+use crate::token::authorizer::*;
+use crate::token::biscuit::*;
+use crate::token::third_party::*;
+use crate::version::*;
+use crate::wasm_result::WasmResult;
+use exports::biscuit::shim::types::{
+    self, DatalogKind, Error, Guest, GuestAuthorizer, GuestAuthorizerBuilder, GuestBiscuit,
+    GuestBiscuitBuilder, GuestBlockBuilder, GuestKeyPair, GuestPrivateKey, GuestPublicKey,
+    GuestThirdPartyBlock, GuestThirdPartyRequest, GuestUnsignedBiscuit, SignatureAlgorithm,
+};
+use std::cell::{Ref, RefCell};
+use std::mem::ManuallyDrop;
+
+wit_bindgen::generate!({
+    world: "shim",
+    path: "wit",
+});
+
+/// Value owned by a resource
+pub struct Resource<T>(RefCell<Box<T>>);
+
+impl<T> Resource<T> {
+    fn new(value: Box<T>) -> Self {
+        Resource(RefCell::new(value))
+    }
+
+    fn get(&self) -> Ref<'_, T> {
+        Ref::map(self.0.borrow(), |value| &**value)
+    }
+
+    fn into_box(self) -> Box<T> {
+        self.0.into_inner()
+    }
+}
+
+impl<T: Clone> Resource<T> {
+    /// Applies a fallible change to a copy of the value, kept only when the call succeeds
+    ///
+    /// Only for the add-code exports: they go through `in_place_apply` and leave their
+    /// builder unusable on error, here the resource keeps its previous value instead.
+    /// A copy that the failed call did not drop is leaked, so exports validating their
+    /// input before touching the builder use `try_update`.
+    fn apply(&self, export: impl FnOnce(&mut WasmResult, &mut T)) -> Result<(), Error> {
+        let mut copy = Box::new(self.get().clone());
+        match read(call(|r| export(r, &mut copy)), WasmResult::into_unit) {
+            Ok(()) => {
+                *self.0.borrow_mut() = copy;
+                Ok(())
+            }
+            Err(error) => {
+                // the copy may have been dropped in place by the failed call, only its
+                // allocation is released so that the destructor does not run a second time
+                drop(unsafe { Box::from_raw(Box::into_raw(copy) as *mut ManuallyDrop<T>) });
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<T> Resource<T> {
+    /// Calls an export leaving the value untouched when it fails
+    fn try_update(&self, export: impl FnOnce(&mut WasmResult, &mut T)) -> Result<(), Error> {
+        let mut value = self.0.borrow_mut();
+        read(call(|r| export(r, &mut value)), WasmResult::into_unit)
+    }
+
+    fn update(&self, export: impl FnOnce(&mut WasmResult, &mut T)) {
+        let mut value = self.0.borrow_mut();
+        infallible(call(|r| export(r, &mut value)).into_unit());
+    }
+}
+
+fn call(export: impl FnOnce(&mut WasmResult)) -> WasmResult {
+    let mut ret = WasmResult::new();
+    export(&mut ret);
+    ret
+}
+
+/// Reads the result of an export, keeping the kind of its error
+fn read<T>(
+    result: WasmResult,
+    read: impl FnOnce(WasmResult) -> Result<T, String>,
+) -> Result<T, Error> {
+    let biscuit_error = result.is_biscuit_error();
+    read(result).map_err(|message| {
+        if biscuit_error {
+            Error::Biscuit(message)
+        } else {
+            Error::Serialization(message)
+        }
+    })
+}
+
+fn infallible<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|message| unreachable!("export without error failed: {message}"))
+}
+
+impl From<SignatureAlgorithm> for crypto::SignatureAlgorithm {
+    fn from(algorithm: SignatureAlgorithm) -> Self {
+        match algorithm {
+            SignatureAlgorithm::Ed25519 => crypto::SignatureAlgorithm::Ed25519,
+            SignatureAlgorithm::Secp256r1 => crypto::SignatureAlgorithm::Secp256r1,
+        }
+    }
+}
+
+impl From<DatalogKind> for datalog::DatalogKind {
+    fn from(kind: DatalogKind) -> Self {
+        match kind {
+            DatalogKind::Block => datalog::DatalogKind::Block,
+            DatalogKind::Authorizer => datalog::DatalogKind::Authorizer,
+            DatalogKind::Fact => datalog::DatalogKind::Fact,
+            DatalogKind::Rule => datalog::DatalogKind::Rule,
+            DatalogKind::Check => datalog::DatalogKind::Check,
+            DatalogKind::Policy => datalog::DatalogKind::Policy,
+        }
+    }
+}
+
+// the component exports are only linked into wasm32 builds, the names
+// of the canonical ABI are not valid symbols for native linkers
+#[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+struct Component;
+
+impl Guest for Component {
+    type PrivateKey = Resource<biscuit_auth::PrivateKey>;
+    type PublicKey = Resource<biscuit_auth::PublicKey>;
+    type KeyPair = Resource<biscuit_auth::KeyPair>;
+    type BlockBuilder = Resource<biscuit_auth::BlockBuilder>;
+    type BiscuitBuilder = Resource<biscuit_auth::BiscuitBuilder>;
+    type UnsignedBiscuit = Resource<UnsignedBiscuit>;
+    type Biscuit = Resource<biscuit_auth::Biscuit>;
+    type ThirdPartyRequest = Resource<biscuit_auth::ThirdPartyRequest>;
+    type ThirdPartyBlock = Resource<biscuit_auth::ThirdPartyBlock>;
+    type AuthorizerBuilder = Resource<biscuit_auth::AuthorizerBuilder>;
+    type Authorizer = Resource<biscuit_auth::Authorizer>;
+
+    fn verify_and_authorize(
+        token: String,
+        public_key: String,
+        authorizer_code: String,
+        params_json: String,
+        limits_json: String,
+    ) -> Result<u32, Error> {
+        read(
+            call(|r| {
+                biscuit_verify_and_authorize(
+                    r,
+                    &token,
+                    &public_key,
+                    &authorizer_code,
+                    &params_json,
+                    &limits_json,
+                )
+            }),
+            WasmResult::into_u32,
+        )
+    }
+
+    fn datalog_validate(code: String, kind: DatalogKind) -> Result<(), Error> {
+        read(
+            call(|r| datalog_validate(r, &code, kind.into())),
+            WasmResult::into_unit,
+        )
+    }
+
+    fn datalog_format(code: String) -> Result<String, Error> {
+        read(call(|r| datalog_format(r, &code)), WasmResult::into_string)
+    }
+
+    fn shim_version() -> String {
+        infallible(call(shim_version).into_string())
+    }
+
+    fn biscuit_auth_version() -> String {
+        infallible(call(biscuit_auth_version).into_string())
+    }
+
+    fn abi_version() -> u32 {
+        infallible(call(abi_version).into_u32())
+    }
+
+    fn shim_features() -> String {
+        infallible(call(shim_features).into_string())
+    }
+}
+
+impl GuestPrivateKey for Resource<biscuit_auth::PrivateKey> {
+    fn from_hex(data: String) -> Result<types::PrivateKey, Error> {
+        read(
+            call(|r| private_key_from_hex(r, &data)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::PrivateKey::new(Resource::new(value)))
+    }
+}
+
+impl GuestPublicKey for Resource<biscuit_auth::PublicKey> {
+    fn from_hex(data: String, algorithm: SignatureAlgorithm) -> Result<types::PublicKey, Error> {
+        read(
+            call(|r| public_key_from_hex(r, &data, algorithm.into())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::PublicKey::new(Resource::new(value)))
+    }
+
+    fn to_hex(&self) -> String {
+        infallible(call(|r| public_key_to_hex(r, &self.get())).into_string())
+    }
+}
+
+impl GuestKeyPair for Resource<biscuit_auth::KeyPair> {
+    fn new(algorithm: SignatureAlgorithm) -> Self {
+        Resource::new(infallible(
+            call(|r| keypair_new(r, algorithm.into())).into_box(),
+        ))
+    }
+
+    fn from_seed(algorithm: SignatureAlgorithm, seed: Vec<u8>) -> Result<types::KeyPair, Error> {
+        read(
+            call(|r| keypair_from_seed(r, algorithm.into(), &seed)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::KeyPair::new(Resource::new(value)))
+    }
+
+    fn from_passphrase(
+        algorithm: SignatureAlgorithm,
+        passphrase: String,
+        salt: Vec<u8>,
+    ) -> Result<types::KeyPair, Error> {
+        read(
+            call(|r| keypair_from_passphrase(r, algorithm.into(), &passphrase, &salt)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::KeyPair::new(Resource::new(value)))
+    }
+
+    fn from_private_key(private_key: types::PrivateKeyBorrow<'_>) -> types::KeyPair {
+        let private_key = private_key.get::<Resource<biscuit_auth::PrivateKey>>();
+        let value =
+            infallible(call(|r| keypair_from_private_key(r, &private_key.get())).into_box());
+        types::KeyPair::new(Resource::new(value))
+    }
+
+    fn public_key(&self) -> types::PublicKey {
+        let value = infallible(call(|r| keypair_public_key(r, &self.get())).into_box());
+        types::PublicKey::new(Resource::new(value))
+    }
+
+    fn private_key(&self) -> types::PrivateKey {
+        let value = infallible(call(|r| keypair_private_key(r, &self.get())).into_box());
+        types::PrivateKey::new(Resource::new(value))
+    }
+
+    fn sign(&self, data: Vec<u8>) -> Result<Vec<u8>, Error> {
+        read(
+            call(|r| keypair_sign(r, &self.get(), &data)),
+            WasmResult::into_bytes,
+        )
+    }
+}
+
+impl GuestBlockBuilder for Resource<biscuit_auth::BlockBuilder> {
+    fn new() -> Self {
+        Resource::new(infallible(call(block_builder_new).into_box()))
+    }
+
+    fn add_code(&self, code: String) -> Result<(), Error> {
+        self.apply(|r, builder| block_builder_add_code(r, builder, &code))
+    }
+
+    fn check_expiration_date(&self, unix_seconds: u64) -> Result<(), Error> {
+        self.try_update(|r, builder| block_builder_check_expiration_date(r, builder, unix_seconds))
+    }
+
+    fn check_resource(&self, name: String) {
+        self.update(|r, builder| block_builder_check_resource(r, builder, &name));
+    }
+
+    fn check_resource_prefix(&self, prefix: String) {
+        self.update(|r, builder| block_builder_check_resource_prefix(r, builder, &prefix));
+    }
+
+    fn check_resource_suffix(&self, suffix: String) {
+        self.update(|r, builder| block_builder_check_resource_suffix(r, builder, &suffix));
+    }
+
+    fn check_operation(&self, operation: String) {
+        self.update(|r, builder| block_builder_check_operation(r, builder, &operation));
+    }
+
+    fn set_context(&self, context: String) {
+        self.update(|r, builder| block_builder_set_context(r, builder, &context));
+    }
+
+    fn to_string(&self) -> String {
+        infallible(call(|r| block_builder_to_string(r, &self.get())).into_string())
+    }
+}
+
+impl GuestBiscuitBuilder for Resource<biscuit_auth::BiscuitBuilder> {
+    fn new() -> Self {
+        Resource::new(infallible(call(biscuit_builder_new).into_box()))
+    }
+
+    fn from_json(json: String) -> Result<types::BiscuitBuilder, Error> {
+        read(
+            call(|r| biscuit_builder_from_json(r, &json)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::BiscuitBuilder::new(Resource::new(value)))
+    }
+
+    fn add_code(&self, code: String) -> Result<(), Error> {
+        self.apply(|r, builder| biscuit_builder_add_code(r, builder, &code))
+    }
+
+    fn add_fact(&self, fact: String) -> Result<(), Error> {
+        self.try_update(|r, builder| biscuit_builder_add_fact(r, builder, &fact))
+    }
+
+    fn add_rule(&self, rule: String) -> Result<(), Error> {
+        self.try_update(|r, builder| biscuit_builder_add_rule(r, builder, &rule))
+    }
+
+    fn add_check(&self, check: String) -> Result<(), Error> {
+        self.try_update(|r, builder| biscuit_builder_add_check(r, builder, &check))
+    }
+
+    fn add_scope_authority(&self) {
+        self.update(biscuit_builder_add_scope_authority);
+    }
+
+    fn add_scope_previous(&self) {
+        self.update(biscuit_builder_add_scope_previous);
+    }
+
+    fn add_scope_public_key(&self, public_key: types::PublicKeyBorrow<'_>) {
+        let public_key = public_key.get::<Resource<biscuit_auth::PublicKey>>();
+        self.update(|r, builder| {
+            biscuit_builder_add_scope_public_key(r, builder, &public_key.get())
+        });
+    }
+
+    fn check_expiration_date(&self, unix_seconds: u64) -> Result<(), Error> {
+        self.try_update(|r, builder| {
+            biscuit_builder_check_expiration_date(r, builder, unix_seconds)
+        })
+    }
+
+    fn check_resource(&self, name: String) {
+        self.update(|r, builder| biscuit_builder_check_resource(r, builder, &name));
+    }
+
+    fn check_resource_prefix(&self, prefix: String) {
+        self.update(|r, builder| biscuit_builder_check_resource_prefix(r, builder, &prefix));
+    }
+
+    fn check_resource_suffix(&self, suffix: String) {
+        self.update(|r, builder| biscuit_builder_check_resource_suffix(r, builder, &suffix));
+    }
+
+    fn check_operation(&self, operation: String) {
+        self.update(|r, builder| biscuit_builder_check_operation(r, builder, &operation));
+    }
+
+    fn merge(&self, other: types::BiscuitBuilder) -> Result<(), Error> {
+        let other = other.into_inner::<Self>().into_box();
+        self.try_update(|r, builder| biscuit_builder_merge(r, builder, other))
+    }
+
+    fn set_root_key_id(&self, root_key_id: u32) {
+        self.update(|r, builder| biscuit_builder_set_root_key_id(r, builder, root_key_id));
+    }
+
+    fn set_context(&self, context: String) {
+        self.update(|r, builder| biscuit_builder_set_context(r, builder, &context));
+    }
+
+    fn to_string(&self) -> String {
+        infallible(call(|r| biscuit_builder_to_string(r, &self.get())).into_string())
+    }
+
+    fn to_json(&self) -> Result<String, Error> {
+        read(
+            call(|r| biscuit_builder_to_json(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+
+    fn build_with_key_pair(
+        builder: types::BiscuitBuilder,
+        root_keypair: types::KeyPairBorrow<'_>,
+    ) -> Result<types::Biscuit, Error> {
+        let builder = builder.into_inner::<Self>().into_box();
+        let root_keypair = root_keypair.get::<Resource<biscuit_auth::KeyPair>>();
+        read(
+            call(|r| biscuit_builder_build_with_key_pair(r, builder, &root_keypair.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+
+    fn build_with_private_key(
+        builder: types::BiscuitBuilder,
+        private_root_key: types::PrivateKeyBorrow<'_>,
+    ) -> Result<types::Biscuit, Error> {
+        let builder = builder.into_inner::<Self>().into_box();
+        let private_root_key = private_root_key.get::<Resource<biscuit_auth::PrivateKey>>();
+        read(
+            call(|r| biscuit_builder_build_with_private_key(r, builder, &private_root_key.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+
+    fn prepare_external_signature(
+        builder: types::BiscuitBuilder,
+        root_public_key: types::PublicKeyBorrow<'_>,
+    ) -> Result<types::UnsignedBiscuit, Error> {
+        let builder = builder.into_inner::<Self>().into_box();
+        let root_public_key = root_public_key.get::<Resource<biscuit_auth::PublicKey>>();
+        read(
+            call(|r| {
+                biscuit_builder_prepare_external_signature(r, builder, &root_public_key.get())
+            }),
+            WasmResult::into_box,
+        )
+        .map(|value| types::UnsignedBiscuit::new(Resource::new(value)))
+    }
+}
+
+impl GuestUnsignedBiscuit for Resource<UnsignedBiscuit> {
+    fn signature_payload(&self) -> Vec<u8> {
+        infallible(call(|r| unsigned_biscuit_signature_payload(r, &self.get())).into_bytes())
+    }
+
+    fn finish(
+        unsigned_biscuit: types::UnsignedBiscuit,
+        signature: Vec<u8>,
+    ) -> Result<types::Biscuit, Error> {
+        let unsigned_biscuit = unsigned_biscuit.into_inner::<Self>().into_box();
+        read(
+            call(|r| unsigned_biscuit_finish(r, unsigned_biscuit, &signature)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+}
+
+impl GuestBiscuit for Resource<biscuit_auth::Biscuit> {
+    fn from_bytes(
+        data: Vec<u8>,
+        root_public_key: types::PublicKeyBorrow<'_>,
+    ) -> Result<types::Biscuit, Error> {
+        let root_public_key = root_public_key.get::<Resource<biscuit_auth::PublicKey>>();
+        read(
+            call(|r| biscuit_from_bytes(r, &data, &root_public_key.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+
+    fn from_base64(
+        data: String,
+        root_public_key: types::PublicKeyBorrow<'_>,
+    ) -> Result<types::Biscuit, Error> {
+        let root_public_key = root_public_key.get::<Resource<biscuit_auth::PublicKey>>();
+        read(
+            call(|r| biscuit_from_base64(r, &data, &root_public_key.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+
+    fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        read(
+            call(|r| biscuit_to_bytes(r, &self.get())),
+            WasmResult::into_bytes,
+        )
+    }
+
+    fn to_base64(&self) -> Result<String, Error> {
+        read(
+            call(|r| biscuit_to_base64(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+
+    fn root_key_id(&self) -> Option<u32> {
+        infallible(call(|r| biscuit_root_key_id(r, &self.get())).into_option_u32())
+    }
+
+    fn block_context(&self, index: u32) -> Result<Option<String>, Error> {
+        read(
+            call(|r| biscuit_block_context(r, &self.get(), index)),
+            WasmResult::into_option_string,
+        )
+    }
+
+    fn append(&self, builder: types::BlockBuilder) -> Result<types::Biscuit, Error> {
+        let builder = builder.into_inner::<Resource<biscuit_auth::BlockBuilder>>();
+        read(
+            call(|r| biscuit_append(r, &self.get(), builder.into_box())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+
+    fn third_party_request(&self) -> Result<types::ThirdPartyRequest, Error> {
+        read(
+            call(|r| biscuit_third_party_request(r, &self.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::ThirdPartyRequest::new(Resource::new(value)))
+    }
+
+    fn append_third_party(
+        &self,
+        external_key: types::PublicKeyBorrow<'_>,
+        block: types::ThirdPartyBlock,
+    ) -> Result<types::Biscuit, Error> {
+        let external_key = external_key.get::<Resource<biscuit_auth::PublicKey>>();
+        let block = block.into_inner::<Resource<biscuit_auth::ThirdPartyBlock>>();
+        read(
+            call(|r| {
+                biscuit_append_third_party(r, &self.get(), &external_key.get(), block.into_box())
+            }),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Biscuit::new(Resource::new(value)))
+    }
+
+    fn authorizer(&self) -> Result<types::Authorizer, Error> {
+        read(
+            call(|r| biscuit_authorizer(r, &self.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Authorizer::new(Resource::new(value)))
+    }
+}
+
+impl GuestThirdPartyRequest for Resource<biscuit_auth::ThirdPartyRequest> {
+    fn from_base64(data: String) -> Result<types::ThirdPartyRequest, Error> {
+        read(
+            call(|r| third_party_request_from_base64(r, &data)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::ThirdPartyRequest::new(Resource::new(value)))
+    }
+
+    fn to_base64(&self) -> Result<String, Error> {
+        read(
+            call(|r| third_party_request_to_base64(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+
+    fn create_block(
+        request: types::ThirdPartyRequest,
+        private_key: types::PrivateKeyBorrow<'_>,
+        builder: types::BlockBuilder,
+    ) -> Result<types::ThirdPartyBlock, Error> {
+        let request = request.into_inner::<Self>().into_box();
+        let private_key = private_key.get::<Resource<biscuit_auth::PrivateKey>>();
+        let builder = builder
+            .into_inner::<Resource<biscuit_auth::BlockBuilder>>()
+            .into_box();
+        read(
+            call(|r| third_party_request_create_block(r, request, &private_key.get(), builder)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::ThirdPartyBlock::new(Resource::new(value)))
+    }
+}
+
+impl GuestThirdPartyBlock for Resource<biscuit_auth::ThirdPartyBlock> {
+    fn to_base64(&self) -> Result<String, Error> {
+        read(
+            call(|r| third_party_block_to_base64(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+}
+
+impl GuestAuthorizerBuilder for Resource<biscuit_auth::AuthorizerBuilder> {
+    fn new() -> Self {
+        Resource::new(infallible(call(authorizer_builder_new).into_box()))
+    }
+
+    fn from_json(json: String) -> Result<types::AuthorizerBuilder, Error> {
+        read(
+            call(|r| authorizer_builder_from_json(r, &json)),
+            WasmResult::into_box,
+        )
+        .map(|value| types::AuthorizerBuilder::new(Resource::new(value)))
+    }
+
+    fn add_code(&self, code: String) -> Result<(), Error> {
+        self.apply(|r, builder| authorizer_builder_add_code(r, builder, &code))
+    }
+
+    fn merge(&self, other: types::AuthorizerBuilder) -> Result<(), Error> {
+        let other = other.into_inner::<Self>().into_box();
+        self.try_update(|r, builder| authorizer_builder_merge(r, builder, other))
+    }
+
+    fn to_string(&self) -> String {
+        infallible(call(|r| authorizer_builder_to_string(r, &self.get())).into_string())
+    }
+
+    fn to_json(&self) -> Result<String, Error> {
+        read(
+            call(|r| authorizer_builder_to_json(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+
+    fn lint(&self) -> Result<String, Error> {
+        read(
+            call(|r| authorizer_builder_lint(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+
+    fn build(
+        builder: types::AuthorizerBuilder,
+        token: types::BiscuitBorrow<'_>,
+    ) -> Result<types::Authorizer, Error> {
+        let builder = builder.into_inner::<Self>().into_box();
+        let token = token.get::<Resource<biscuit_auth::Biscuit>>();
+        read(
+            call(|r| authorizer_builder_build(r, builder, &token.get())),
+            WasmResult::into_box,
+        )
+        .map(|value| types::Authorizer::new(Resource::new(value)))
+    }
+
+    fn authorize_batch(
+        &self,
+        max_time: u64,
+        tokens: Vec<u8>,
+        root_public_key: types::PublicKeyBorrow<'_>,
+    ) -> Result<Vec<u8>, Error> {
+        let run_limits = infallible(call(|r| authorizer_run_limits(r, max_time)).into_box());
+        let root_public_key = root_public_key.get::<Resource<biscuit_auth::PublicKey>>();
+        read(
+            call(|r| authorize_batch(r, &self.get(), &run_limits, &tokens, &root_public_key.get())),
+            WasmResult::into_bytes,
+        )
+    }
+}
+
+impl GuestAuthorizer for Resource<biscuit_auth::Authorizer> {
+    fn authorize(&self, max_time: u64) -> Result<u32, Error> {
+        let run_limits = infallible(call(|r| authorizer_run_limits(r, max_time)).into_box());
+        let mut authorizer = self.0.borrow_mut();
+        read(
+            call(|r| authorizer_authorize(r, &mut authorizer, run_limits)),
+            WasmResult::into_u32,
+        )
+    }
+
+    fn print_world(&self) -> String {
+        infallible(call(|r| authorizer_print_world(r, &self.get())).into_string())
+    }
+
+    fn dump_json(&self) -> Result<String, Error> {
+        read(
+            call(|r| authorizer_dump_json(r, &self.get())),
+            WasmResult::into_string,
+        )
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+export!(Component);
@@ -1,7 +1,11 @@
 use crate::crypto::SignatureAlgorithm;
 use crate::wasm_result::WasmResult;
 use crate::{make_rng, wasm_export};
-use biscuit_auth::{KeyPair, PrivateKey, PublicKey};
+use biscuit_auth::error::Format;
+use biscuit_auth::{Algorithm, KeyPair, PrivateKey, PublicKey};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use zeroize::Zeroizing;
 
 // create a new keypair with the given signature algorithm
 // Input:
@@ -20,7 +24,72 @@ wasm_export!(
     }
 );
 
+// create a keypair from a 32 bytes seed, the same seed always gives the same keypair
+// the seed is used as the private key, for Secp256r1 it must be a valid scalar
+// Input:
+// signature_algorithm is an u32 value
+// seed {ptr, len} is the 32 bytes seed
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the keypair allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn keypair_from_seed(
+        signature_algorithm: SignatureAlgorithm,
+        seed: &[u8],
+    ) -> Result<Box<KeyPair>, Format> {
+        let algorithm: Algorithm = signature_algorithm.into();
+        Ok(Box::new(KeyPair::from_bytes(seed, algorithm.into())?))
+    }
+);
+
+// derive a keypair from a passphrase and a salt with HKDF-SHA256, meant for development environments
+// HKDF is not a password hashing function, do not use it to protect production root keys
+// Input:
+// signature_algorithm is an u32 value
+// passphrase {ptr, len} is the passphrase
+// salt {ptr, len} is the salt, the same passphrase gives different keypairs for different salts
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the keypair allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn keypair_from_passphrase(
+        signature_algorithm: SignatureAlgorithm,
+        passphrase: &str,
+        salt: &[u8],
+    ) -> Result<Box<KeyPair>, Format> {
+        let algorithm: Algorithm = signature_algorithm.into();
+        // the algorithm is part of the info so that both algorithms never share a seed
+        let info = match algorithm {
+            Algorithm::Ed25519 => "biscuit-wasm-shim/ed25519-private",
+            Algorithm::Secp256r1 => "biscuit-wasm-shim/secp256r1-private",
+        };
+        let mut seed = Zeroizing::new([0u8; 32]);
+        Hkdf::<Sha256>::new(Some(salt), passphrase.as_bytes())
+            .expand(info.as_bytes(), seed.as_mut())
+            .map_err(|e| Format::InvalidKey(e.to_string()))?;
+        Ok(Box::new(KeyPair::from_bytes(
+            seed.as_ref(),
+            algorithm.into(),
+        )?))
+    }
+);
+
 // drop the keypair and free the memory allocated for it
+// the key material is wiped on drop by biscuit-auth (ed25519-dalek and ecdsa signing keys are ZeroizeOnDrop)
 // Input:
 // keypair is a pointer to the keypair allocated in the wasm memory
 // Output:
@@ -75,3 +144,28 @@ wasm_export!(
         Box::new(KeyPair::from(private_key))
     }
 );
+
+// sign arbitrary bytes with the keypair
+// software stand-in for an external signer, e.g. to complete an unsigned biscuit locally
+// Input:
+// keypair is a pointer to the keypair allocated in the wasm memory
+// data {ptr, len} is the payload to sign
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the signature allocated in the wasm memory
+// (64 bytes for Ed25519, DER encoded ECDSA signature for Secp256r1)
+// data_len is the length of the signature in bytes
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn keypair_sign(
+        keypair: &KeyPair,
+        data: &[u8],
+    ) -> Result<Vec<u8>, biscuit_auth::error::Format> {
+        Ok(keypair.sign(data)?.to_bytes().to_vec())
+    }
+);
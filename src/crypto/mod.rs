@@ -1,10 +1,10 @@
 use biscuit_auth::Algorithm;
 
-mod keypair;
-mod private_key;
-mod public_key;
+pub(crate) mod keypair;
+pub(crate) mod private_key;
+pub(crate) mod public_key;
 
-#[derive(Default)]
+#[derive(Default, Clone, Copy)]
 #[repr(C)]
 #[allow(non_camel_case_types)]
 pub enum SignatureAlgorithm {
@@ -1,9 +1,12 @@
+use crate::secret::SecretString;
 use crate::wasm_export;
 use crate::wasm_result::WasmResult;
 use biscuit_auth::PrivateKey;
+use core::fmt::Write;
 use core::str::FromStr;
 
 // drop the private key and free the memory allocated for it
+// the key material is wiped on drop by biscuit-auth (ed25519-dalek and ecdsa signing keys are ZeroizeOnDrop)
 // Input:
 // keypair is a pointer to the private key allocated in the wasm memory
 // Output:
@@ -25,15 +28,30 @@ wasm_export!(
 // data is the private key in hex format
 // data_len is the length of the hexadecimal representation of the private key in bytes
 // kind is Ok because the function never fails
+//
+// the string must be released with secret_string_free(data, data_len) which wipes it before freeing
 wasm_export!(
-    fn private_key_to_hex(private_key: &PrivateKey) -> String {
-        private_key.to_prefixed_string()
+    fn private_key_to_hex(private_key: &PrivateKey) -> SecretString {
+        let algorithm = match private_key {
+            PrivateKey::Ed25519(_) => "ed25519-private",
+            PrivateKey::P256(_) => "secp256r1-private",
+        };
+        let bytes = private_key.to_bytes();
+        // allocate the exact size up front, growing the string would leave copies of the key behind
+        let mut hex = String::with_capacity(algorithm.len() + 1 + bytes.len() * 2);
+        hex.push_str(algorithm);
+        hex.push('/');
+        for byte in bytes.iter() {
+            let _ = write!(hex, "{byte:02x}");
+        }
+        SecretString::from(hex)
     }
 );
 
 // Create a private key from a hexadecimal string
 // Input:
-// data is the private key in hex format
+// data is the private key in hex format, prefixed with its algorithm
+// (`ed25519-private/<hex>` or `secp256r1-private/<hex>`, as returned by private_key_to_hex)
 // Output:
 // returnArea { data, data_len, kind }
 //
@@ -2,13 +2,26 @@ use crate::crypto::SignatureAlgorithm;
 use crate::wasm_export;
 use crate::wasm_result::WasmResult;
 use biscuit_auth::PublicKey;
+use core::str::FromStr;
+
+/// Parses a hex encoded public key, the algorithm is only used when the key is not prefixed
+pub(crate) fn parse_public_key(
+    data: &str,
+    algorithm: SignatureAlgorithm,
+) -> Result<PublicKey, biscuit_auth::error::Format> {
+    if data.contains('/') {
+        PublicKey::from_str(data)
+    } else {
+        PublicKey::from_bytes_hex(data, algorithm.into())
+    }
+}
 
 // Format the public key as a hexadecimal string
 // Input:
 // private_key is a pointer to the public key allocated in the wasm memory
 // Output:
 // returnArea { data, data_len, kind=Ok }
-// data is the public key in hex format
+// data is the public key in hex format, prefixed with its algorithm (`ed25519/<hex>` or `secp256r1/<hex>`)
 // data_len is the length of the hexadecimal representation of the public key in bytes
 // kind is Ok because the function never fails
 wasm_export!(
@@ -19,7 +32,9 @@ wasm_export!(
 
 // Create a public key from a hexadecimal string
 // Input:
-// data is the public key in hex format
+// data is the public key in hex format, optionally prefixed with its algorithm
+// (`ed25519/<hex>` or `secp256r1/<hex>`, as returned by public_key_to_hex)
+// algorithm is the signature algorithm of the key, ignored when data is prefixed
 // Output:
 // returnArea { data, data_len, kind }
 //
@@ -35,6 +50,6 @@ wasm_export!(
         data: &str,
         algorithm: SignatureAlgorithm,
     ) -> Result<Box<PublicKey>, biscuit_auth::error::Format> {
-        Ok(Box::new(PublicKey::from_bytes_hex(data, algorithm.into())?))
+        Ok(Box::new(parse_public_key(data, algorithm)?))
     }
 );
@@ -0,0 +1,293 @@
+use crate::builder::{parse_check, parse_fact, parse_policy, parse_rule};
+use crate::wasm_export;
+use crate::wasm_result::WasmResult;
+use biscuit_auth::builder::{Check, Fact, Policy, Rule, Scope};
+use biscuit_auth::error::Token;
+use biscuit_parser::error::{LanguageError, ParseErrors};
+use biscuit_parser::parser::{SourceResult, parse_block_source, parse_source};
+use serde::Serialize;
+
+/// Kind of datalog code
+// the variants are only constructed by the host
+#[allow(dead_code)]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub enum DatalogKind {
+    /// facts, rules and checks of a token block, optionally preceded by scopes
+    Block = 0,
+    /// facts, rules, checks and policies of an authorizer
+    Authorizer = 1,
+    Fact = 2,
+    Rule = 3,
+    Check = 4,
+    Policy = 5,
+}
+
+/// Position in the source code, lines and columns start at 1, columns count characters
+#[derive(Serialize)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+    /// offset in bytes
+    pub offset: usize,
+}
+
+impl Position {
+    pub(crate) fn new(code: &str, offset: usize) -> Self {
+        let before = &code[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Position {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            offset,
+        }
+    }
+}
+
+/// Error found in datalog code, spanning from `start` to `end`
+#[derive(Serialize)]
+pub struct DatalogError {
+    pub message: String,
+    pub start: Position,
+    pub end: Position,
+}
+
+impl DatalogError {
+    fn new(code: &str, start: usize, end: usize, message: String) -> Self {
+        DatalogError {
+            message,
+            start: Position::new(code, start),
+            end: Position::new(code, end),
+        }
+    }
+
+    /// Error on a part of `code`, `part` must be a slice of `code`
+    fn at(code: &str, part: &str, message: String) -> Self {
+        let (start, end) = span(code, part);
+        DatalogError {
+            message,
+            start,
+            end,
+        }
+    }
+}
+
+/// Start and end of a part of `code`, `part` must be a slice of `code`
+pub(crate) fn span(code: &str, part: &str) -> (Position, Position) {
+    let start = (part.as_ptr() as usize)
+        .checked_sub(code.as_ptr() as usize)
+        .filter(|start| start + part.len() <= code.len())
+        .unwrap_or(0);
+    (
+        Position::new(code, start),
+        Position::new(code, start + part.len()),
+    )
+}
+
+/// The parser reports what it could not parse, up to the end of the statement
+fn parse_error_message(input: &str, message: Option<String>) -> String {
+    message.unwrap_or_else(|| match input.chars().next() {
+        Some(_) => format!("unexpected input: '{input}'"),
+        None => "unexpected end of input".to_string(),
+    })
+}
+
+/// Converts the error of a single element parser, it reports the remaining input
+fn element_errors<T>(code: &str, result: Result<T, Token>) -> Vec<DatalogError> {
+    let Err(error) = result else {
+        return Vec::new();
+    };
+    match error {
+        Token::Language(LanguageError::ParseError(ParseErrors { errors })) => errors
+            .into_iter()
+            .map(|error| {
+                let start = code.len().saturating_sub(error.input.len());
+                let input = error.input.split(';').next().unwrap_or_default();
+                let message = parse_error_message(input, error.message);
+                DatalogError::new(code, start, start + input.len(), message)
+            })
+            .collect(),
+        error => vec![DatalogError::new(code, 0, code.len(), error.to_string())],
+    }
+}
+
+/// Converts the errors of a source parser, they point to the invalid statement
+fn parse_errors(code: &str, errors: Vec<biscuit_parser::parser::Error<'_>>) -> Vec<DatalogError> {
+    // after an error, the parser skips to the next statement and reports
+    // the whitespace that may follow the last one as another error
+    errors
+        .into_iter()
+        .enumerate()
+        .filter(|(index, error)| *index == 0 || !error.input.trim().is_empty())
+        .map(|(_, error)| {
+            DatalogError::at(
+                code,
+                error.input,
+                parse_error_message(error.input, error.message),
+            )
+        })
+        .collect()
+}
+
+/// Parses a list of statements, then validates each of them as the builders would
+fn source_errors<'a>(
+    code: &'a str,
+    parse: impl Fn(&'a str) -> Result<SourceResult<'a>, Vec<biscuit_parser::parser::Error<'a>>>,
+) -> Vec<DatalogError> {
+    let source = match parse(code) {
+        Ok(source) => source,
+        Err(errors) => return parse_errors(code, errors),
+    };
+
+    let mut errors = Vec::new();
+    let mut report = |part: &str, result: Result<(), Token>| {
+        if let Err(error) = result {
+            errors.push(DatalogError::at(code, part, error.to_string()));
+        }
+    };
+    for (part, fact) in source.facts {
+        report(part, Fact::from(fact).validate());
+    }
+    for (part, rule) in source.rules {
+        report(part, Rule::from(rule).validate_parameters());
+    }
+    for (part, check) in source.checks {
+        report(part, Check::from(check).validate_parameters());
+    }
+    for (part, policy) in source.policies {
+        report(part, Policy::from(policy).validate_parameters());
+    }
+    errors
+}
+
+// validate datalog code without building anything, e.g. to lint it in an editor
+// Input:
+// code {ptr, len}: the datalog code
+// kind: the kind of code (0 = block, 1 = authorizer, 2 = fact, 3 = rule, 4 = check, 5 = policy)
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+//
+// if kind = ErrBiscuit
+// data is the pointer to the list of errors allocated in the wasm memory, as JSON:
+// [{ "message": "..", "start": { "line", "column", "offset" }, "end": { "line", "column", "offset" } }]
+// lines and columns start at 1, columns count characters and offsets count bytes
+// data_len is the length of the list of errors in bytes
+//
+// if kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn datalog_validate(code: &str, kind: DatalogKind) -> Result<(), Vec<DatalogError>> {
+        let errors = match kind {
+            DatalogKind::Block => source_errors(code, parse_block_source),
+            DatalogKind::Authorizer => source_errors(code, parse_source),
+            DatalogKind::Fact => element_errors(code, parse_fact(code)),
+            DatalogKind::Rule => element_errors(code, parse_rule(code)),
+            DatalogKind::Check => element_errors(code, parse_check(code)),
+            DatalogKind::Policy => element_errors(code, parse_policy(code)),
+        };
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+);
+
+/// Prints each statement on its own line, sections separated by an empty line
+///
+/// Facts and rules are sorted and deduplicated since their order has no meaning, checks
+/// and policies keep theirs: policies apply in order and checks are reported by index.
+fn format_source(source: SourceResult<'_>) -> String {
+    fn sorted(mut statements: Vec<String>) -> Vec<String> {
+        statements.sort_unstable();
+        statements.dedup();
+        statements
+    }
+
+    let scopes = source
+        .scopes
+        .into_iter()
+        .map(|scope| Scope::from(scope).to_string())
+        .collect::<Vec<_>>();
+    let sections = [
+        if scopes.is_empty() {
+            Vec::new()
+        } else {
+            vec![format!("trusting {}", scopes.join(", "))]
+        },
+        sorted(
+            source
+                .facts
+                .into_iter()
+                .map(|(_, fact)| Fact::from(fact).to_string())
+                .collect(),
+        ),
+        sorted(
+            source
+                .rules
+                .into_iter()
+                .map(|(_, rule)| Rule::from(rule).to_string())
+                .collect(),
+        ),
+        source
+            .checks
+            .into_iter()
+            .map(|(_, check)| Check::from(check).to_string())
+            .collect(),
+        source
+            .policies
+            .into_iter()
+            .map(|(_, policy)| Policy::from(policy).to_string())
+            .collect(),
+    ];
+
+    sections
+        .iter()
+        .filter(|section| !section.is_empty())
+        .map(|section| {
+            section
+                .iter()
+                .map(|statement| format!("{statement};\n"))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// format datalog code in a canonical form, e.g. to keep reviews of policies focused
+// one statement per line, with scopes, facts, rules, checks then policies separated
+// by an empty line; facts and rules are sorted, comments are removed
+// Input:
+// code {ptr, len}: the datalog code of a block or of an authorizer
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the formatted code allocated in the wasm memory
+// data_len is the length of the formatted code in bytes
+//
+// if kind = ErrBiscuit
+// data is the pointer to the list of parse errors allocated in the wasm memory,
+// as JSON, in the same format as datalog_validate
+// data_len is the length of the list of errors in bytes
+//
+// if kind = ErrSerialization
+// data is the pointer to the error message allocated in the wasm memory
+// data_len is the length of the error message in bytes
+wasm_export!(
+    fn datalog_format(code: &str) -> Result<String, Vec<DatalogError>> {
+        // only blocks can start with scopes, only authorizers can have policies
+        let source = match parse_block_source(code) {
+            Ok(source) => source,
+            Err(_) => parse_source(code).map_err(|errors| parse_errors(code, errors))?,
+        };
+        Ok(format_source(source))
+    }
+);
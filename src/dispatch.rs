@@ -0,0 +1,510 @@
+//! Single entry point calling the exports by name
+//!
+//! A request is a JSON object `{"id": .., "method": "<export name>", "params": {..}}`,
+//! the parameters are named after the ones of the export:
+//! - strings are JSON strings, byte arrays are hex strings, numbers are JSON numbers
+//! - signature algorithms are `"ed25519"` or `"secp256r1"`, datalog kinds are
+//!   `"block"`, `"authorizer"`, `"fact"`, `"rule"`, `"check"` or `"policy"`
+//! - keys, builders, tokens and authorizers are handle ids returned by previous calls
+//!
+//! The response is `{"id": .., "result": ..}` or `{"id": .., "error": ..}`. Opaque
+//! values are returned as handle ids, released by the matching `*_drop` method or
+//! consumed by the methods taking ownership of them, as the exports do.
+//!
+//! `private_key_to_hex` puts a private key in the response, so every response is a
+//! `SecretString`, written without intermediate copies and wiped when released.
+
+use crate::builder::authorizer_builder::*;
+use crate::builder::biscuit_builder::*;
+use crate::builder::block_builder::*;
+#[cfg(feature = "extern-funcs")]
+use crate::builder::extern_func::*;
+use crate::builder::external_signature::*;
+use crate::crypto::SignatureAlgorithm;
+use crate::crypto::keypair::*;
+use crate::crypto::private_key::*;
+use crate::crypto::public_key::*;
+use crate::datalog::*;
+use crate::secret::SecretString;
+use crate::term_json::decode_hex;
+use crate::token::authorizer::*;
+use crate::token::biscuit::*;
+use crate::token::third_party::*;
+use crate::version::*;
+use crate::wasm_export;
+use crate::wasm_result::WasmResult;
+use biscuit_auth::datalog::RunLimits;
+use biscuit_auth::{
+    Authorizer, AuthorizerBuilder, Biscuit, BiscuitBuilder, BlockBuilder, KeyPair, PrivateKey,
+    PublicKey, ThirdPartyBlock, ThirdPartyRequest,
+};
+use serde_json::{Map, Value, json};
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::mem::ManuallyDrop;
+use zeroize::Zeroize;
+
+/// Values owned by the dispatcher, referenced by the host with their handle id
+#[derive(Default)]
+struct Handles {
+    next: u32,
+    values: HashMap<u32, Box<dyn Any>>,
+}
+
+impl Handles {
+    fn insert<T: 'static>(&mut self, value: Box<T>) -> u32 {
+        // ids start at 1 and are never reused, so a stale id cannot reach another value
+        self.next += 1;
+        self.values.insert(self.next, value);
+        self.next
+    }
+
+    /// Pointer to the value of a handle, stable until the handle is removed
+    fn pointer<T: 'static>(&mut self, id: u32) -> Result<*mut T, Value> {
+        let value = self
+            .values
+            .get_mut(&id)
+            .ok_or_else(|| dispatch_error(format!("unknown handle {id}")))?;
+        value
+            .downcast_mut::<T>()
+            .map(|value| value as *mut T)
+            .ok_or_else(|| {
+                dispatch_error(format!(
+                    "handle {id} is not a {}",
+                    std::any::type_name::<T>()
+                ))
+            })
+    }
+
+    /// Removes a handle whose type was checked by `pointer`
+    fn take<T: 'static>(&mut self, id: u32) -> Box<T> {
+        self.values
+            .remove(&id)
+            .and_then(|value| value.downcast().ok())
+            .expect("handle checked before being taken")
+    }
+
+    /// Removes a handle whose value was already dropped in place by a failed call
+    fn forget<T: 'static>(&mut self, id: u32) {
+        let dropped = Box::into_raw(self.take::<T>(id));
+        // releases the allocation without running the destructor a second time
+        drop(unsafe { Box::from_raw(dropped as *mut ManuallyDrop<T>) });
+    }
+}
+
+thread_local! {
+    static HANDLES: RefCell<Handles> = RefCell::new(Handles::default());
+}
+
+fn dispatch_error(message: String) -> Value {
+    json!({ "Dispatch": message })
+}
+
+/// Error of an export, JSON when the kind is ErrBiscuit, plain text when ErrSerialization
+fn export_error(message: String) -> Value {
+    serde_json::from_str(&message).unwrap_or(Value::String(message))
+}
+
+fn param<'a>(params: &'a Map<String, Value>, name: &str) -> Result<&'a Value, Value> {
+    params
+        .get(name)
+        .ok_or_else(|| dispatch_error(format!("missing parameter {name}")))
+}
+
+fn invalid(name: &str, expected: &str) -> Value {
+    dispatch_error(format!("parameter {name} must be {expected}"))
+}
+
+fn str_param<'a>(params: &'a Map<String, Value>, name: &str) -> Result<&'a str, Value> {
+    param(params, name)?
+        .as_str()
+        .ok_or_else(|| invalid(name, "a string"))
+}
+
+fn bytes_param(params: &Map<String, Value>, name: &str) -> Result<Vec<u8>, Value> {
+    decode_hex(str_param(params, name)?).map_err(|_| invalid(name, "a hex string"))
+}
+
+fn u64_param(params: &Map<String, Value>, name: &str) -> Result<u64, Value> {
+    param(params, name)?
+        .as_u64()
+        .ok_or_else(|| invalid(name, "an unsigned integer"))
+}
+
+fn u32_param(params: &Map<String, Value>, name: &str) -> Result<u32, Value> {
+    u32::try_from(u64_param(params, name)?).map_err(|_| invalid(name, "a 32 bits integer"))
+}
+
+fn algorithm_param(params: &Map<String, Value>, name: &str) -> Result<SignatureAlgorithm, Value> {
+    match str_param(params, name)? {
+        "ed25519" => Ok(SignatureAlgorithm::Ed25519),
+        "secp256r1" => Ok(SignatureAlgorithm::Secp256r1),
+        _ => Err(invalid(name, "ed25519 or secp256r1")),
+    }
+}
+
+fn kind_param(params: &Map<String, Value>, name: &str) -> Result<DatalogKind, Value> {
+    match str_param(params, name)? {
+        "block" => Ok(DatalogKind::Block),
+        "authorizer" => Ok(DatalogKind::Authorizer),
+        "fact" => Ok(DatalogKind::Fact),
+        "rule" => Ok(DatalogKind::Rule),
+        "check" => Ok(DatalogKind::Check),
+        "policy" => Ok(DatalogKind::Policy),
+        _ => Err(invalid(
+            name,
+            "block, authorizer, fact, rule, check or policy",
+        )),
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Rejects a call passing the same handle twice, the export would get aliased values
+fn distinct_handles(ids: &[Option<u32>]) -> Result<(), Value> {
+    let mut ids: Vec<u32> = ids.iter().flatten().copied().collect();
+    ids.sort_unstable();
+    match ids.windows(2).find(|pair| pair[0] == pair[1]) {
+        Some(pair) => Err(dispatch_error(format!("handle {} passed twice", pair[0]))),
+        None => Ok(()),
+    }
+}
+
+// parameter kinds:
+// - Str, Bytes, U64, U32, Algorithm, Kind: values read from the request
+// - Ref<T>, Mut<T>: handles borrowed by the call
+// - Take<T>: handles consumed by the call
+// - Apply<T>: handles borrowed by a call going through `in_place_apply`, whose
+//   value is dropped in place when the call fails
+macro_rules! read_param {
+    ($params:ident, $name:ident, Str) => {
+        str_param(&$params, stringify!($name))?
+    };
+    ($params:ident, $name:ident, Bytes) => {
+        bytes_param(&$params, stringify!($name))?
+    };
+    ($params:ident, $name:ident, U64) => {
+        u64_param(&$params, stringify!($name))?
+    };
+    ($params:ident, $name:ident, U32) => {
+        u32_param(&$params, stringify!($name))?
+    };
+    ($params:ident, $name:ident, Algorithm) => {
+        algorithm_param(&$params, stringify!($name))?
+    };
+    ($params:ident, $name:ident, Kind) => {
+        kind_param(&$params, stringify!($name))?
+    };
+    ($params:ident, $name:ident, $handle:ident<$t:ty>) => {
+        u32_param(&$params, stringify!($name))?
+    };
+}
+
+macro_rules! handle_id {
+    ($name:ident, $handle:ident<$t:ty>) => {
+        Some($name)
+    };
+    ($name:ident, $kind:ident) => {
+        None
+    };
+}
+
+macro_rules! bind_param {
+    ($handles:ident, $name:ident, $handle:ident<$t:ty>) => {
+        ($name, $handles.pointer::<$t>($name)?)
+    };
+    ($handles:ident, $name:ident, $kind:ident) => {
+        $name
+    };
+}
+
+macro_rules! pass_param {
+    ($handles:ident, $name:ident, Bytes) => {
+        &$name
+    };
+    ($handles:ident, $name:ident, Ref<$t:ty>) => {
+        unsafe { &*$name.1 }
+    };
+    ($handles:ident, $name:ident, Mut<$t:ty>) => {
+        unsafe { &mut *$name.1 }
+    };
+    ($handles:ident, $name:ident, Apply<$t:ty>) => {
+        unsafe { &mut *$name.1 }
+    };
+    ($handles:ident, $name:ident, Take<$t:ty>) => {
+        $handles.take::<$t>($name.0)
+    };
+    ($handles:ident, $name:ident, $kind:ident) => {
+        $name
+    };
+}
+
+macro_rules! release_param {
+    ($handles:ident, $name:ident, $failed:ident, Apply<$t:ty>) => {
+        if $failed {
+            $handles.forget::<$t>($name.0);
+        }
+    };
+    ($handles:ident, $name:ident, $failed:ident, $kind:ident $(<$t:ty>)?) => {};
+}
+
+// result kinds: Handle<T>, Text, OptionText, Json (a JSON document returned as a string), Hex, U32,
+// OptionU32, Unit
+macro_rules! read_result {
+    ($handles:ident, $result:ident, Handle<$t:ty>) => {
+        $result
+            .into_box::<$t>()
+            .map(|value| json!($handles.insert(value)))
+    };
+    ($handles:ident, $result:ident, Text) => {
+        $result.into_string().map(Value::String)
+    };
+    ($handles:ident, $result:ident, OptionText) => {
+        $result.into_option_string().map(|value| json!(value))
+    };
+    ($handles:ident, $result:ident, Json) => {
+        $result
+            .into_string()
+            .map(|json| serde_json::from_str(&json).unwrap_or(Value::String(json)))
+    };
+    ($handles:ident, $result:ident, Hex) => {
+        $result.into_bytes().map(|bytes| json!(encode_hex(&bytes)))
+    };
+    ($handles:ident, $result:ident, U32) => {
+        $result.into_u32().map(|value| json!(value))
+    };
+    ($handles:ident, $result:ident, OptionU32) => {
+        $result.into_option_u32().map(|value| json!(value))
+    };
+    ($handles:ident, $result:ident, Unit) => {
+        $result.into_unit().map(|()| Value::Null)
+    };
+}
+
+macro_rules! methods {
+    ($(
+        $(#[$attr:meta])*
+        $method:ident($($name:ident: $kind:ident $(<$t:ty>)?),* $(,)?) -> $ret:ident $(<$rt:ty>)?;
+    )*) => {
+        fn call_method(
+            handles: &mut Handles,
+            method: &str,
+            params: &Map<String, Value>,
+        ) -> Result<Value, Value> {
+            match method {
+                $(
+                    $(#[$attr])*
+                    stringify!($method) => {
+                        $(let $name = read_param!(params, $name, $kind $(<$t>)?);)*
+                        distinct_handles(&[$(handle_id!($name, $kind $(<$t>)?)),*])?;
+                        $(let $name = bind_param!(handles, $name, $kind $(<$t>)?);)*
+                        let mut result = WasmResult::new();
+                        $method(&mut result, $(pass_param!(handles, $name, $kind $(<$t>)?)),*);
+                        let value = read_result!(handles, result, $ret $(<$rt>)?);
+                        #[allow(unused_variables)]
+                        let failed = value.is_err();
+                        $(release_param!(handles, $name, failed, $kind $(<$t>)?);)*
+                        value.map_err(export_error)
+                    }
+                )*
+                _ => Err(dispatch_error(format!("unknown method {method}"))),
+            }
+        }
+    };
+}
+
+methods! {
+    // keys
+    keypair_new(signature_algorithm: Algorithm) -> Handle<KeyPair>;
+    keypair_from_seed(signature_algorithm: Algorithm, seed: Bytes) -> Handle<KeyPair>;
+    keypair_from_passphrase(signature_algorithm: Algorithm, passphrase: Str, salt: Bytes) -> Handle<KeyPair>;
+    keypair_from_private_key(private_key: Ref<PrivateKey>) -> Handle<KeyPair>;
+    keypair_public_key(keypair: Ref<KeyPair>) -> Handle<PublicKey>;
+    keypair_private_key(keypair: Ref<KeyPair>) -> Handle<PrivateKey>;
+    keypair_sign(keypair: Ref<KeyPair>, data: Bytes) -> Hex;
+    keypair_drop(keypair: Take<KeyPair>) -> Unit;
+    public_key_from_hex(data: Str, algorithm: Algorithm) -> Handle<PublicKey>;
+    public_key_to_hex(public_key: Ref<PublicKey>) -> Text;
+    private_key_from_hex(data: Str) -> Handle<PrivateKey>;
+    private_key_to_hex(private_key: Ref<PrivateKey>) -> Text;
+    private_key_drop(private_key: Take<PrivateKey>) -> Unit;
+
+    // biscuit builders
+    biscuit_builder_new() -> Handle<BiscuitBuilder>;
+    biscuit_builder() -> Handle<BiscuitBuilder>;
+    biscuit_builder_from_json(json: Str) -> Handle<BiscuitBuilder>;
+    biscuit_builder_add_code(builder: Apply<BiscuitBuilder>, code: Str) -> Unit;
+    biscuit_builder_add_fact(builder: Mut<BiscuitBuilder>, fact: Str) -> Unit;
+    biscuit_builder_add_rule(builder: Mut<BiscuitBuilder>, rule: Str) -> Unit;
+    biscuit_builder_add_check(builder: Mut<BiscuitBuilder>, check: Str) -> Unit;
+    biscuit_builder_add_scope_authority(builder: Mut<BiscuitBuilder>) -> Unit;
+    biscuit_builder_add_scope_previous(builder: Mut<BiscuitBuilder>) -> Unit;
+    biscuit_builder_add_scope_public_key(builder: Mut<BiscuitBuilder>, public_key: Ref<PublicKey>) -> Unit;
+    biscuit_builder_check_expiration_date(builder: Mut<BiscuitBuilder>, unix_seconds: U64) -> Unit;
+    biscuit_builder_check_resource(builder: Mut<BiscuitBuilder>, name: Str) -> Unit;
+    biscuit_builder_check_resource_prefix(builder: Mut<BiscuitBuilder>, prefix: Str) -> Unit;
+    biscuit_builder_check_resource_suffix(builder: Mut<BiscuitBuilder>, suffix: Str) -> Unit;
+    biscuit_builder_check_operation(builder: Mut<BiscuitBuilder>, operation: Str) -> Unit;
+    biscuit_builder_merge(builder: Mut<BiscuitBuilder>, other: Take<BiscuitBuilder>) -> Unit;
+    biscuit_builder_set_root_key_id(builder: Mut<BiscuitBuilder>, root_key_id: U32) -> Unit;
+    biscuit_builder_set_context(builder: Mut<BiscuitBuilder>, context: Str) -> Unit;
+    biscuit_builder_to_string(builder: Ref<BiscuitBuilder>) -> Text;
+    biscuit_builder_to_json(builder: Ref<BiscuitBuilder>) -> Json;
+    biscuit_builder_build_with_private_key(builder: Take<BiscuitBuilder>, private_root_key: Ref<PrivateKey>) -> Handle<Biscuit>;
+    biscuit_builder_build_with_key_pair(builder: Take<BiscuitBuilder>, root_keypair: Ref<KeyPair>) -> Handle<Biscuit>;
+    biscuit_builder_prepare_external_signature(builder: Take<BiscuitBuilder>, root_public_key: Ref<PublicKey>) -> Handle<UnsignedBiscuit>;
+    biscuit_builder_drop(builder: Take<BiscuitBuilder>) -> Unit;
+    unsigned_biscuit_signature_payload(unsigned_biscuit: Ref<UnsignedBiscuit>) -> Hex;
+    unsigned_biscuit_finish(unsigned_biscuit: Take<UnsignedBiscuit>, signature: Bytes) -> Handle<Biscuit>;
+    unsigned_biscuit_drop(unsigned_biscuit: Take<UnsignedBiscuit>) -> Unit;
+
+    // block builders
+    block_builder_new() -> Handle<BlockBuilder>;
+    block_builder_add_code(builder: Apply<BlockBuilder>, code: Str) -> Unit;
+    block_builder_check_expiration_date(builder: Mut<BlockBuilder>, unix_seconds: U64) -> Unit;
+    block_builder_check_resource(builder: Mut<BlockBuilder>, name: Str) -> Unit;
+    block_builder_check_resource_prefix(builder: Mut<BlockBuilder>, prefix: Str) -> Unit;
+    block_builder_check_resource_suffix(builder: Mut<BlockBuilder>, suffix: Str) -> Unit;
+    block_builder_check_operation(builder: Mut<BlockBuilder>, operation: Str) -> Unit;
+    block_builder_set_context(builder: Mut<BlockBuilder>, context: Str) -> Unit;
+    block_builder_to_string(builder: Ref<BlockBuilder>) -> Text;
+    block_builder_drop(builder: Take<BlockBuilder>) -> Unit;
+
+    // authorizer builders
+    authorizer_builder_new() -> Handle<AuthorizerBuilder>;
+    authorizer_builder_from_json(json: Str) -> Handle<AuthorizerBuilder>;
+    authorizer_builder_add_code(builder: Apply<AuthorizerBuilder>, code: Str) -> Unit;
+    authorizer_builder_merge(builder: Mut<AuthorizerBuilder>, other: Take<AuthorizerBuilder>) -> Unit;
+    #[cfg(feature = "extern-funcs")]
+    authorizer_builder_register_extern_func(builder: Mut<AuthorizerBuilder>, name: Str) -> Unit;
+    authorizer_builder_to_string(builder: Ref<AuthorizerBuilder>) -> Text;
+    authorizer_builder_to_json(builder: Ref<AuthorizerBuilder>) -> Json;
+    authorizer_builder_lint(builder: Ref<AuthorizerBuilder>) -> Json;
+    authorizer_builder_build(builder: Take<AuthorizerBuilder>, token: Ref<Biscuit>) -> Handle<Authorizer>;
+    authorizer_builder_drop(builder: Take<AuthorizerBuilder>) -> Unit;
+
+    // tokens
+    biscuit_from_bytes(data: Bytes, root_public_key: Ref<PublicKey>) -> Handle<Biscuit>;
+    biscuit_from_base64(data: Str, root_public_key: Ref<PublicKey>) -> Handle<Biscuit>;
+    biscuit_to_bytes(biscuit: Ref<Biscuit>) -> Hex;
+    biscuit_to_base64(biscuit: Ref<Biscuit>) -> Text;
+    biscuit_root_key_id(biscuit: Ref<Biscuit>) -> OptionU32;
+    biscuit_block_context(biscuit: Ref<Biscuit>, index: U32) -> OptionText;
+    biscuit_append(biscuit: Ref<Biscuit>, builder: Take<BlockBuilder>) -> Handle<Biscuit>;
+    biscuit_authorizer(biscuit: Ref<Biscuit>) -> Handle<Authorizer>;
+    biscuit_verify_and_authorize(token: Str, public_key: Str, authorizer_code: Str, params_json: Str, limits_json: Str) -> U32;
+    biscuit_drop(biscuit: Take<Biscuit>) -> Unit;
+
+    // third party blocks
+    biscuit_third_party_request(biscuit: Ref<Biscuit>) -> Handle<ThirdPartyRequest>;
+    biscuit_append_third_party(biscuit: Ref<Biscuit>, external_key: Ref<PublicKey>, block: Take<ThirdPartyBlock>) -> Handle<Biscuit>;
+    third_party_request_from_base64(data: Str) -> Handle<ThirdPartyRequest>;
+    third_party_request_to_base64(request: Ref<ThirdPartyRequest>) -> Text;
+    third_party_request_create_block(request: Take<ThirdPartyRequest>, private_key: Ref<PrivateKey>, builder: Take<BlockBuilder>) -> Handle<ThirdPartyBlock>;
+    third_party_request_drop(request: Take<ThirdPartyRequest>) -> Unit;
+    third_party_block_to_base64(block: Ref<ThirdPartyBlock>) -> Text;
+    third_party_block_drop(block: Take<ThirdPartyBlock>) -> Unit;
+
+    // authorizers
+    authorizer_run_limits(max_time: U64) -> Handle<RunLimits>;
+    authorizer_authorize(authorizer: Mut<Authorizer>, run_limits: Take<RunLimits>) -> U32;
+    authorizer_print_world(authorizer: Ref<Authorizer>) -> Text;
+    authorizer_dump_json(authorizer: Ref<Authorizer>) -> Json;
+    authorize_batch(builder: Ref<AuthorizerBuilder>, run_limits: Ref<RunLimits>, tokens: Bytes, root_public_key: Ref<PublicKey>) -> Hex;
+    authorizer_drop(authorizer: Take<Authorizer>) -> Unit;
+
+    // datalog tooling
+    datalog_validate(code: Str, kind: Kind) -> Unit;
+    datalog_format(code: Str) -> Text;
+
+    // versions
+    shim_version() -> Text;
+    biscuit_auth_version() -> Text;
+    abi_version() -> U32;
+    shim_features() -> Json;
+}
+
+/// Parses a request, runs it and builds the response
+fn handle_request(handles: &mut Handles, request: &str) -> Value {
+    let request: Value = match serde_json::from_str(request) {
+        Ok(request) => request,
+        Err(e) => {
+            return json!({ "id": null, "error": dispatch_error(format!("invalid request: {e}")) });
+        }
+    };
+    let id = request.get("id").cloned().unwrap_or(Value::Null);
+    let empty = Map::new();
+    let outcome = match (request.get("method"), request.get("params")) {
+        (Some(Value::String(method)), None) => call_method(handles, method, &empty),
+        (Some(Value::String(method)), Some(Value::Object(params))) => {
+            call_method(handles, method, params)
+        }
+        (Some(Value::String(_)), Some(_)) => {
+            Err(dispatch_error("params must be an object".to_string()))
+        }
+        _ => Err(dispatch_error("method must be a string".to_string())),
+    };
+    match outcome {
+        Ok(result) => json!({ "id": id, "result": result }),
+        Err(error) => json!({ "id": id, "error": error }),
+    }
+}
+
+/// Writer counting the bytes of a response, to allocate it at its exact size
+struct ByteCounter(usize);
+
+impl io::Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serializes a response without reallocating, then wipes the result it was built from
+fn into_secret(mut response: Value) -> SecretString {
+    let mut counter = ByteCounter(0);
+    serde_json::to_writer(&mut counter, &response).expect("a JSON value always serializes");
+    // growing the buffer would leave copies of the result behind
+    let mut json = Vec::with_capacity(counter.0);
+    serde_json::to_writer(&mut json, &response).expect("a JSON value always serializes");
+    if let Some(Value::String(result)) = response.get_mut("result") {
+        result.zeroize();
+    }
+    SecretString::from(String::from_utf8(json).expect("serde_json writes UTF-8"))
+}
+
+// call any operation of the shim by name
+// Input:
+// request {ptr, len}: the JSON request {"id", "method", "params"}, see src/dispatch.rs
+//
+// Output:
+// returnArea { data, data_len, kind=Ok }
+// data is the pointer to the JSON response {"id", "result"} or {"id", "error"} allocated in the wasm memory
+// data_len is the length of the response in bytes
+// kind is Ok because errors are reported in the response
+//
+// the response must be released with secret_string_free(data, data_len) which wipes it before freeing,
+// as it can hold a private key
+wasm_export!(
+    fn dispatch(request: &str) -> SecretString {
+        HANDLES.with(|handles| {
+            let response = match handles.try_borrow_mut() {
+                Ok(mut handles) => handle_request(&mut handles, request),
+                // an extern function implemented by the host cannot dispatch again
+                Err(_) => json!({
+                    "id": null,
+                    "error": dispatch_error("dispatch is not reentrant".to_string()),
+                }),
+            };
+            into_secret(response)
+        })
+    }
+);
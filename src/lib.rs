@@ -3,11 +3,27 @@ extern crate core;
 
 mod allocation;
 mod builder;
+#[cfg(feature = "component")]
+mod component;
 mod crypto;
+mod datalog;
+#[cfg(feature = "dispatch")]
+mod dispatch;
+#[cfg(feature = "manifest")]
+pub mod manifest;
+mod panic;
+mod secret;
+mod snapshot;
+mod term_json;
+#[cfg(test)]
+mod tests;
 mod token;
+mod version;
 mod wasm_export;
 mod wasm_result;
 
+// the entropy comes from the `__getrandom_custom` import on wasm32-unknown-unknown
+// and from `random_get` on WASI targets
 #[allow(unused)]
 pub(crate) fn make_rng() -> rand::rngs::StdRng {
     let mut data = [0u8; 8];
@@ -18,6 +34,15 @@ pub(crate) fn make_rng() -> rand::rngs::StdRng {
 unsafe extern "C" {
     #[cfg(feature = "print")]
     pub fn print(ptr: *const u8, len: usize);
+
+    #[cfg(feature = "extern-funcs")]
+    pub fn extern_func_call(
+        name_ptr: *const u8,
+        name_len: usize,
+        args_ptr: *const u8,
+        args_len: usize,
+        result: &mut wasm_result::WasmResult,
+    );
 }
 
 #[macro_export]
@@ -0,0 +1,377 @@
+//! ABI manifest: every `wasm_export!` records its name, its parameters and its result,
+//! written as JSON and as a C header by `examples/manifest.rs`
+//!
+//! Parameters and results are described through the `ExportParam` and `ExportResult`
+//! traits, implemented for every type used in an export signature.
+
+use crate::crypto::SignatureAlgorithm;
+use crate::datalog::DatalogKind;
+use crate::secret::SecretString;
+use crate::version::ABI_VERSION;
+use serde::Serialize;
+use std::fmt::Write;
+
+/// Description of an export, recorded by `wasm_export!`
+#[derive(Debug, Serialize)]
+pub struct Export {
+    pub name: &'static str,
+    /// module defining the export
+    #[serde(skip)]
+    pub module: &'static str,
+    /// parameters after the return area
+    pub params: &'static [Param],
+    pub result: Output,
+}
+
+inventory::collect!(Export);
+
+#[derive(Debug, Serialize)]
+pub struct Param {
+    pub name: &'static str,
+    #[serde(flatten)]
+    pub ty: Type,
+    pub ownership: Ownership,
+}
+
+impl Param {
+    pub const fn new<T: ExportParam>(name: &'static str) -> Self {
+        Param {
+            name,
+            ty: T::TYPE,
+            ownership: T::OWNERSHIP,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type", content = "type_name")]
+pub enum Type {
+    /// UTF-8 string passed as a pointer and a length
+    Str,
+    /// byte array passed as a pointer and a length
+    Bytes,
+    U32,
+    U64,
+    /// `#[repr(C)]` enum passed as an u32
+    Enum(&'static str),
+    /// pointer to a value allocated by the shim
+    Opaque(&'static str),
+    /// string released with `secret_string_free`
+    Secret,
+    Unit,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Ownership {
+    /// copied by the call
+    Value,
+    /// read during the call, still owned by the host
+    Borrowed,
+    /// modified during the call, still owned by the host
+    BorrowedMut,
+    /// `Box<T>` owned by the shim from then on, the pointer must not be used again
+    Consumed,
+}
+
+/// Content of the return area
+#[derive(Debug, Serialize)]
+pub struct Output {
+    #[serde(flatten)]
+    pub ty: Type,
+    /// `OkNone` is returned when there is no value
+    pub optional: bool,
+    /// `ErrBiscuit` or `ErrSerialization` can be returned
+    ///
+    /// With the `catch-panic` feature, every export can also return `ErrPanic`,
+    /// whatever this flag says
+    pub fallible: bool,
+}
+
+impl Output {
+    pub const fn of<T: ExportResult>() -> Self {
+        Output {
+            ty: T::TYPE,
+            optional: T::OPTIONAL,
+            fallible: T::FALLIBLE,
+        }
+    }
+}
+
+pub trait ExportParam {
+    const TYPE: Type;
+    const OWNERSHIP: Ownership;
+}
+
+pub trait ExportResult {
+    const TYPE: Type;
+    const OPTIONAL: bool = false;
+    const FALLIBLE: bool = false;
+}
+
+/// Types passed between the host and the shim as opaque pointers
+pub trait Opaque {
+    const NAME: &'static str;
+}
+
+macro_rules! opaque {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(impl Opaque for $ty {
+            const NAME: &'static str = $name;
+        })*
+    };
+}
+
+opaque!(
+    biscuit_auth::KeyPair => "KeyPair",
+    biscuit_auth::PrivateKey => "PrivateKey",
+    biscuit_auth::PublicKey => "PublicKey",
+    biscuit_auth::BlockBuilder => "BlockBuilder",
+    biscuit_auth::BiscuitBuilder => "BiscuitBuilder",
+    crate::builder::external_signature::UnsignedBiscuit => "UnsignedBiscuit",
+    biscuit_auth::Biscuit => "Biscuit",
+    biscuit_auth::ThirdPartyRequest => "ThirdPartyRequest",
+    biscuit_auth::ThirdPartyBlock => "ThirdPartyBlock",
+    biscuit_auth::AuthorizerBuilder => "AuthorizerBuilder",
+    biscuit_auth::Authorizer => "Authorizer",
+    biscuit_auth::datalog::RunLimits => "RunLimits",
+);
+
+impl ExportParam for &str {
+    const TYPE: Type = Type::Str;
+    const OWNERSHIP: Ownership = Ownership::Borrowed;
+}
+
+impl ExportParam for &[u8] {
+    const TYPE: Type = Type::Bytes;
+    const OWNERSHIP: Ownership = Ownership::Borrowed;
+}
+
+impl ExportParam for u32 {
+    const TYPE: Type = Type::U32;
+    const OWNERSHIP: Ownership = Ownership::Value;
+}
+
+impl ExportParam for u64 {
+    const TYPE: Type = Type::U64;
+    const OWNERSHIP: Ownership = Ownership::Value;
+}
+
+impl ExportParam for SignatureAlgorithm {
+    const TYPE: Type = Type::Enum("SignatureAlgorithm");
+    const OWNERSHIP: Ownership = Ownership::Value;
+}
+
+impl ExportParam for DatalogKind {
+    const TYPE: Type = Type::Enum("DatalogKind");
+    const OWNERSHIP: Ownership = Ownership::Value;
+}
+
+impl<T: Opaque> ExportParam for &T {
+    const TYPE: Type = Type::Opaque(T::NAME);
+    const OWNERSHIP: Ownership = Ownership::Borrowed;
+}
+
+impl<T: Opaque> ExportParam for &mut T {
+    const TYPE: Type = Type::Opaque(T::NAME);
+    const OWNERSHIP: Ownership = Ownership::BorrowedMut;
+}
+
+impl<T: Opaque> ExportParam for Box<T> {
+    const TYPE: Type = Type::Opaque(T::NAME);
+    const OWNERSHIP: Ownership = Ownership::Consumed;
+}
+
+impl ExportResult for () {
+    const TYPE: Type = Type::Unit;
+}
+
+impl ExportResult for u32 {
+    const TYPE: Type = Type::U32;
+}
+
+impl ExportResult for String {
+    const TYPE: Type = Type::Str;
+}
+
+impl ExportResult for Vec<u8> {
+    const TYPE: Type = Type::Bytes;
+}
+
+impl ExportResult for SecretString {
+    const TYPE: Type = Type::Secret;
+}
+
+impl<T: Opaque> ExportResult for Box<T> {
+    const TYPE: Type = Type::Opaque(T::NAME);
+}
+
+impl<T: ExportResult> ExportResult for Option<T> {
+    const TYPE: Type = T::TYPE;
+    const OPTIONAL: bool = true;
+    const FALLIBLE: bool = T::FALLIBLE;
+}
+
+impl<T: ExportResult, E> ExportResult for Result<T, E> {
+    const TYPE: Type = T::TYPE;
+    const OPTIONAL: bool = T::OPTIONAL;
+    const FALLIBLE: bool = true;
+}
+
+/// Recorded exports, sorted by name
+///
+/// The exports defined by the native tests are not part of the module and are left out
+pub fn exports() -> Vec<&'static Export> {
+    const TESTS: &str = concat!(env!("CARGO_CRATE_NAME"), "::tests::");
+    let mut exports: Vec<_> = inventory::iter::<Export>
+        .into_iter()
+        .filter(|export| !export.module.starts_with(TESTS))
+        .collect();
+    exports.sort_by_key(|export| export.name);
+    exports
+}
+
+#[derive(Serialize)]
+struct Manifest {
+    abi_version: u32,
+    exports: Vec<&'static Export>,
+}
+
+pub fn to_json() -> String {
+    let manifest = Manifest {
+        abi_version: ABI_VERSION,
+        exports: exports(),
+    };
+    serde_json::to_string_pretty(&manifest).unwrap() + "\n"
+}
+
+/// Types and enums shared by every export, with the wasm32 layout
+///
+/// The header is read by hosts, which are not wasm32 programs: pointers and lengths are
+/// the `uint32_t` offsets and sizes of the linear memory, not native pointers
+const HEADER: &str = r#"/* Generated from the `wasm_export!` manifest of biscuit-wasm-shim, do not edit */
+
+#ifndef BISCUIT_WASM_SHIM_H
+#define BISCUIT_WASM_SHIM_H
+
+#include <stdint.h>
+
+/* pointers and lengths are offsets and sizes in the linear memory of the module */
+
+/* ErrPanic is returned by any export when the module is built with catch-panic */
+typedef enum {
+    ResultKind_Ok = 0,
+    ResultKind_ErrBiscuit = 1,
+    ResultKind_ErrSerialization = 2,
+    ResultKind_OkNone = 3,
+    ResultKind_ErrPanic = 4,
+} ResultKind;
+
+/* return area, passed first to every export */
+typedef struct {
+    uint32_t ptr;
+    uint32_t len;
+    uint32_t kind;
+} WasmResult;
+
+_Static_assert(sizeof(WasmResult) == 12, "WasmResult is 12 bytes on wasm32");
+
+typedef enum {
+    SignatureAlgorithm_Ed25519 = 0,
+    SignatureAlgorithm_Secp256r1 = 1,
+} SignatureAlgorithm;
+
+typedef enum {
+    DatalogKind_Block = 0,
+    DatalogKind_Authorizer = 1,
+    DatalogKind_Fact = 2,
+    DatalogKind_Rule = 3,
+    DatalogKind_Check = 4,
+    DatalogKind_Policy = 5,
+} DatalogKind;
+"#;
+
+/// Allocation functions, exported next to the operations
+///
+/// `malloc` and `free` would clash with the declarations of `<stdlib.h>`, they are
+/// declared with the names of the WASI module
+const ALLOCATION: &str = r#"
+/* exported as malloc and free by the wasm32-unknown-unknown module,
+ * as shim_malloc and shim_free by the wasm32-wasip1 module */
+uint32_t shim_malloc(uint32_t size, uint32_t align);
+void shim_free(uint32_t ptr, uint32_t size, uint32_t align);
+uint32_t get_return_area(void);
+void wasm_result_free(uint32_t result);
+void secret_string_free(uint32_t ptr, uint32_t len);
+"#;
+
+fn c_result(output: &Output) -> String {
+    let value = match output.ty {
+        Type::Unit => "nothing".to_string(),
+        Type::U32 => "an u32 in ptr".to_string(),
+        Type::Str => "a string, released with wasm_result_free".to_string(),
+        Type::Bytes => "bytes, released with wasm_result_free".to_string(),
+        Type::Secret => "a secret string, released with secret_string_free".to_string(),
+        Type::Opaque(name) => format!("the offset of a {name} in ptr, owned by the caller"),
+        Type::U64 | Type::Enum(_) => unreachable!("not returned by exports"),
+    };
+    let mut result = format!("returns {value}");
+    if output.optional {
+        result.push_str(", or OkNone");
+    }
+    if output.fallible {
+        result.push_str(", or an error");
+    }
+    result
+}
+
+pub fn to_c_header() -> String {
+    let exports = exports();
+    let mut header = HEADER.to_string();
+    let _ = writeln!(
+        header,
+        "\n#define BISCUIT_WASM_SHIM_ABI_VERSION {ABI_VERSION}"
+    );
+
+    let mut opaque: Vec<_> = exports
+        .iter()
+        .flat_map(|export| export.params.iter().map(|param| param.ty))
+        .chain(exports.iter().map(|export| export.result.ty))
+        .filter_map(|ty| match ty {
+            Type::Opaque(name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    opaque.sort();
+    opaque.dedup();
+    header.push_str("\n/* values allocated by the shim, referenced by their offset */\n");
+    for name in opaque {
+        let _ = writeln!(header, "typedef uint32_t {name};");
+    }
+    header.push_str(ALLOCATION);
+
+    for export in exports {
+        let _ = write!(header, "\n/* {}", c_result(&export.result));
+        for param in export.params {
+            if let (Type::Opaque(name), Ownership::Consumed) = (param.ty, param.ownership) {
+                let _ = write!(header, "\n * {} ({name}) is consumed", param.name);
+            }
+        }
+        header.push_str(" */\n");
+
+        let mut params = vec!["uint32_t ret".to_string()];
+        for param in export.params {
+            let name = param.name;
+            params.push(match param.ty {
+                Type::Str | Type::Bytes => format!("uint32_t {name}_ptr, uint32_t {name}_len"),
+                Type::U32 => format!("uint32_t {name}"),
+                Type::U64 => format!("uint64_t {name}"),
+                Type::Enum(ty) | Type::Opaque(ty) => format!("{ty} {name}"),
+                Type::Secret | Type::Unit => unreachable!("not taken by exports"),
+            });
+        }
+        let _ = writeln!(header, "void {}({});", export.name, params.join(", "));
+    }
+    header.push_str("\n#endif\n");
+    header
+}
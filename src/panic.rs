@@ -0,0 +1,58 @@
+//! Panic reporting
+//!
+//! The release profile aborts on panic, which the host only sees as a trap: the hook
+//! forwards the message and location through the `print` import first. With the
+//! `catch-panic` feature and `panic = "unwind"`, a panic in an export body is returned
+//! as an `ErrPanic` result instead.
+
+#[cfg(feature = "catch-panic")]
+use crate::wasm_result::{IntoWasmResult, WasmResult};
+#[cfg(feature = "catch-panic")]
+use std::cell::RefCell;
+use std::sync::Once;
+
+#[cfg(feature = "catch-panic")]
+thread_local! {
+    /// message of the last panic, with its location
+    static LAST_PANIC: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Installs the panic hook, called at the start of every export
+pub fn install_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            // "panicked at src/lib.rs:1:2:\n<message>"
+            #[cfg_attr(
+                not(any(feature = "print", feature = "catch-panic")),
+                allow(unused_variables)
+            )]
+            let message = info.to_string();
+            crate::print_wasm!("{message}");
+            #[cfg(feature = "catch-panic")]
+            LAST_PANIC.with(|last| *last.borrow_mut() = Some(message));
+            default_hook(info);
+        }));
+    });
+}
+
+/// Runs an export body, a panic fills the return area with `ErrPanic` and its message
+///
+/// Values borrowed mutably by the call may be left half updated or dropped in place,
+/// as after an error of `in_place_apply`, the host must not use them again.
+#[cfg(feature = "catch-panic")]
+pub fn catch<T: IntoWasmResult>(ret: &mut WasmResult, body: impl FnOnce() -> T) {
+    LAST_PANIC.with(|last| last.borrow_mut().take());
+    match std::panic::catch_unwind(std::panic::AssertUnwindSafe(body)) {
+        Ok(value) => ret.capture(value),
+        Err(payload) => {
+            let message = LAST_PANIC
+                .with(|last| last.borrow_mut().take())
+                .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "panicked".to_string());
+            ret.capture_panic(message);
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use zeroize::Zeroize;
+
+/// String holding secret material (e.g. a private key in hex format)
+///
+/// The string is stored as a `Box<str>` so that its allocation size is
+/// always its length: the host can release it with `secret_string_free`
+/// knowing only the `{ data, data_len }` pair of the returnArea.
+/// The bytes are wiped before the memory is released.
+pub struct SecretString(Box<str>);
+
+impl SecretString {
+    /// Leaks the string, the host is responsible for calling `secret_string_free`
+    pub(crate) fn into_raw(self) -> (*const u8, usize) {
+        let this = core::mem::ManuallyDrop::new(self);
+        (this.0.as_ptr(), this.0.len())
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        // `into_boxed_str` reallocates when the capacity exceeds the length,
+        // which would leave a copy of the secret behind, so callers should
+        // allocate the exact size up front
+        debug_assert_eq!(value.len(), value.capacity());
+        SecretString(value.into_boxed_str())
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[unsafe(no_mangle)]
+/// Wipes and frees a secret string returned by the shim
+/// *Parameters*
+/// - `ptr`: data of the returnArea
+/// - `len`: data_len of the returnArea
+pub fn secret_string_free(ptr: *mut u8, len: usize) {
+    let secret =
+        unsafe { Box::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len) as *mut str) };
+    drop(SecretString(secret));
+}
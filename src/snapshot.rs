@@ -0,0 +1,143 @@
+//! Printing and conversion of the datalog stored in authorizer snapshots
+//!
+//! Builders and authorizers only expose their content, with its origin,
+//! through their serialized snapshot.
+
+use biscuit_auth::PublicKey;
+use biscuit_auth::builder::{Policy, Scope};
+use biscuit_auth::datalog::SymbolTable;
+use biscuit_auth::error::Format;
+use biscuit_auth::format::{convert, schema};
+
+/// Prints the elements of a snapshot with the symbols of its world
+pub(crate) struct SnapshotPrinter {
+    symbols: SymbolTable,
+}
+
+impl SnapshotPrinter {
+    pub(crate) fn new(world: &schema::AuthorizerWorld) -> Result<Self, Format> {
+        let public_keys = world
+            .public_keys
+            .iter()
+            .map(PublicKey::from_proto)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(SnapshotPrinter {
+            symbols: SymbolTable::from_symbols_and_public_keys(world.symbols.clone(), public_keys)?,
+        })
+    }
+
+    pub(crate) fn facts(&self, facts: &[schema::Fact]) -> Result<Vec<String>, Format> {
+        facts
+            .iter()
+            .map(|fact| {
+                Ok(self
+                    .symbols
+                    .print_fact(&convert::proto_fact_to_token_fact(fact)?))
+            })
+            .collect()
+    }
+
+    pub(crate) fn rules(
+        &self,
+        rules: &[schema::Rule],
+        version: u32,
+    ) -> Result<Vec<String>, Format> {
+        rules
+            .iter()
+            .map(|rule| {
+                let (rule, _) = convert::proto_rule_to_token_rule(rule, version)?;
+                Ok(self.symbols.print_rule(&rule))
+            })
+            .collect()
+    }
+
+    pub(crate) fn checks(
+        &self,
+        checks: &[schema::Check],
+        version: u32,
+    ) -> Result<Vec<String>, Format> {
+        checks
+            .iter()
+            .map(|check| {
+                Ok(self
+                    .symbols
+                    .print_check(&convert::proto_check_to_token_check(check, version)?))
+            })
+            .collect()
+    }
+
+    /// Policies converted back to builder policies
+    pub(crate) fn builder_policies(
+        &self,
+        policies: &[schema::Policy],
+        version: u32,
+    ) -> Result<Vec<Policy>, Format> {
+        policies
+            .iter()
+            .map(|policy| convert::proto_policy_to_policy(policy, &self.symbols, version))
+            .collect()
+    }
+
+    pub(crate) fn policies(
+        &self,
+        policies: &[schema::Policy],
+        version: u32,
+    ) -> Result<Vec<String>, Format> {
+        Ok(self
+            .builder_policies(policies, version)?
+            .iter()
+            .map(ToString::to_string)
+            .collect())
+    }
+}
+
+/// Prints the scopes of a serialized block, public keys are indexes in `public_keys`
+pub(crate) fn print_scopes(
+    scopes: &[schema::Scope],
+    public_keys: &[schema::PublicKey],
+) -> Result<Vec<String>, Format> {
+    Ok(read_scopes(scopes, public_keys)?
+        .iter()
+        .map(ToString::to_string)
+        .collect())
+}
+
+/// Converts the scopes of a serialized block, public keys are indexes in `public_keys`
+pub(crate) fn read_scopes(
+    scopes: &[schema::Scope],
+    public_keys: &[schema::PublicKey],
+) -> Result<Vec<Scope>, Format> {
+    scopes
+        .iter()
+        .map(|scope| {
+            Ok(match scope.content {
+                Some(schema::scope::Content::ScopeType(scope_type))
+                    if scope_type == schema::scope::ScopeType::Authority as i32 =>
+                {
+                    Scope::Authority
+                }
+                Some(schema::scope::Content::ScopeType(scope_type))
+                    if scope_type == schema::scope::ScopeType::Previous as i32 =>
+                {
+                    Scope::Previous
+                }
+                Some(schema::scope::Content::PublicKey(index)) => {
+                    let key = usize::try_from(index)
+                        .ok()
+                        .and_then(|index| public_keys.get(index))
+                        .ok_or_else(|| {
+                            Format::DeserializationError(
+                                "deserialization error: invalid public key index".to_string(),
+                            )
+                        })?;
+                    Scope::PublicKey(PublicKey::from_proto(key)?)
+                }
+                _ => {
+                    return Err(Format::DeserializationError(
+                        "deserialization error: invalid scope".to_string(),
+                    ));
+                }
+            })
+        })
+        .collect()
+}
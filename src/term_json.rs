@@ -0,0 +1,127 @@
+//! Datalog terms exchanged with the host as JSON
+//!
+//! - integers, strings, booleans, null and arrays use the matching JSON value
+//! - dates are `{ "date": <seconds since the Unix epoch> }`
+//! - byte arrays are `{ "bytes": "<hex>" }`
+//! - sets are `{ "set": [..] }`
+//! - maps are `{ "map": [[<integer or string key>, <value>], ..] }`
+
+use biscuit_auth::builder::{MapKey, Term};
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, BTreeSet};
+
+fn tagged(tag: &str, value: Value) -> Value {
+    let mut object = Map::new();
+    object.insert(tag.to_string(), value);
+    Value::Object(object)
+}
+
+#[cfg_attr(not(any(test, feature = "extern-funcs")), allow(dead_code))]
+pub(crate) fn term_to_json(term: &Term) -> Result<Value, String> {
+    Ok(match term {
+        Term::Integer(i) => json!(i),
+        Term::Str(s) => json!(s),
+        Term::Bool(b) => json!(b),
+        Term::Null => Value::Null,
+        Term::Date(d) => tagged("date", json!(d)),
+        Term::Bytes(bytes) => tagged(
+            "bytes",
+            json!(bytes.iter().map(|b| format!("{b:02x}")).collect::<String>()),
+        ),
+        Term::Array(terms) => {
+            Value::Array(terms.iter().map(term_to_json).collect::<Result<_, _>>()?)
+        }
+        Term::Set(terms) => tagged(
+            "set",
+            Value::Array(terms.iter().map(term_to_json).collect::<Result<_, _>>()?),
+        ),
+        Term::Map(map) => tagged(
+            "map",
+            Value::Array(
+                map.iter()
+                    .map(|(key, value)| {
+                        let key = match key {
+                            MapKey::Integer(i) => json!(i),
+                            MapKey::Str(s) => json!(s),
+                            MapKey::Parameter(p) => {
+                                return Err(format!("unbound parameter {{{p}}}"));
+                            }
+                        };
+                        Ok(json!([key, term_to_json(value)?]))
+                    })
+                    .collect::<Result<_, String>>()?,
+            ),
+        ),
+        Term::Variable(v) => return Err(format!("unbound variable ${v}")),
+        Term::Parameter(p) => return Err(format!("unbound parameter {{{p}}}")),
+    })
+}
+
+pub(crate) fn term_from_json(value: Value) -> Result<Term, String> {
+    Ok(match value {
+        Value::Null => Term::Null,
+        Value::Bool(b) => Term::Bool(b),
+        Value::Number(n) => Term::Integer(
+            n.as_i64()
+                .ok_or_else(|| format!("{n} is not a 64 bits integer"))?,
+        ),
+        Value::String(s) => Term::Str(s),
+        Value::Array(values) => Term::Array(
+            values
+                .into_iter()
+                .map(term_from_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Object(object) => {
+            let mut entries = object.into_iter();
+            let (Some((tag, value)), None) = (entries.next(), entries.next()) else {
+                return Err("expected an object with a single key".to_string());
+            };
+            match (tag.as_str(), value) {
+                ("date", Value::Number(n)) => {
+                    Term::Date(n.as_u64().ok_or_else(|| format!("invalid date {n}"))?)
+                }
+                ("bytes", Value::String(hex)) => Term::Bytes(decode_hex(&hex)?),
+                ("set", Value::Array(values)) => Term::Set(
+                    values
+                        .into_iter()
+                        .map(term_from_json)
+                        .collect::<Result<BTreeSet<_>, _>>()?,
+                ),
+                ("map", Value::Array(entries)) => Term::Map(
+                    entries
+                        .into_iter()
+                        .map(|entry| {
+                            let Value::Array(entry) = entry else {
+                                return Err("expected a [key, value] map entry".to_string());
+                            };
+                            let [key, value]: [Value; 2] = entry
+                                .try_into()
+                                .map_err(|_| "expected a [key, value] map entry".to_string())?;
+                            let key = match key {
+                                Value::String(s) => MapKey::Str(s),
+                                Value::Number(n) => MapKey::Integer(
+                                    n.as_i64()
+                                        .ok_or_else(|| format!("{n} is not a 64 bits integer"))?,
+                                ),
+                                _ => return Err("map keys are integers or strings".to_string()),
+                            };
+                            Ok((key, term_from_json(value)?))
+                        })
+                        .collect::<Result<BTreeMap<_, _>, _>>()?,
+                ),
+                (tag, _) => return Err(format!("unexpected term `{tag}`")),
+            }
+        }
+    })
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Result<Vec<u8>, String> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return Err(format!("invalid hex string {hex}"));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|e| e.to_string()))
+        .collect()
+}
@@ -0,0 +1,213 @@
+//! Ed25519 / Secp256r1 parity: every flow runs with both algorithms and with mixed chains
+
+use crate::builder::authorizer_builder::*;
+use crate::builder::biscuit_builder::*;
+use crate::builder::block_builder::*;
+use crate::crypto::SignatureAlgorithm;
+use crate::crypto::keypair::*;
+use crate::crypto::private_key::*;
+use crate::crypto::public_key::*;
+use crate::tests::call;
+use crate::token::authorizer::*;
+use crate::token::biscuit::*;
+use crate::token::third_party::*;
+use biscuit_auth::{
+    Authorizer, AuthorizerBuilder, Biscuit, BiscuitBuilder, BlockBuilder, KeyPair, PrivateKey,
+    PublicKey, ThirdPartyBlock, ThirdPartyRequest,
+};
+
+pub(super) const ALGORITHMS: [SignatureAlgorithm; 2] =
+    [SignatureAlgorithm::Ed25519, SignatureAlgorithm::Secp256r1];
+
+pub(super) fn new_keypair(algorithm: SignatureAlgorithm) -> Box<KeyPair> {
+    call(|r| keypair_new(r, algorithm)).into_box().unwrap()
+}
+
+pub(super) fn public_key(keypair: &KeyPair) -> Box<PublicKey> {
+    call(|r| keypair_public_key(r, keypair)).into_box().unwrap()
+}
+
+fn public_key_hex(keypair: &KeyPair) -> String {
+    let public_key = public_key(keypair);
+    call(|r| public_key_to_hex(r, &public_key))
+        .into_string()
+        .unwrap()
+}
+
+fn block_builder(code: &str) -> Box<BlockBuilder> {
+    let mut builder: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    call(|r| block_builder_add_code(r, &mut builder, code))
+        .into_unit()
+        .unwrap();
+    builder
+}
+
+pub(super) fn mint(root: &KeyPair, code: &str) -> Box<Biscuit> {
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_add_code(r, &mut builder, code))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_build_with_key_pair(r, builder, root))
+        .into_box()
+        .unwrap()
+}
+
+/// Serializes the token and parses it back with the hex encoded root public key
+pub(super) fn reload(biscuit: &Biscuit, root: &KeyPair) -> Box<Biscuit> {
+    let token = call(|r| biscuit_to_base64(r, biscuit))
+        .into_string()
+        .unwrap();
+    let root_public_key_hex = public_key_hex(root);
+    // the algorithm is read from the prefix, the one passed here is ignored
+    let root_public_key: Box<PublicKey> =
+        call(|r| public_key_from_hex(r, &root_public_key_hex, SignatureAlgorithm::Ed25519))
+            .into_box()
+            .unwrap();
+    call(|r| biscuit_from_base64(r, &token, &root_public_key))
+        .into_box()
+        .unwrap()
+}
+
+pub(super) fn authorize(biscuit: &Biscuit, code: &str) -> Result<u32, String> {
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| authorizer_builder_add_code(r, &mut builder, code))
+        .into_unit()
+        .unwrap();
+    let mut authorizer: Box<Authorizer> = call(|r| authorizer_builder_build(r, builder, biscuit))
+        .into_box()
+        .unwrap();
+    let run_limits = call(|r| authorizer_run_limits(r, 10_000))
+        .into_box()
+        .unwrap();
+    let result = call(|r| authorizer_authorize(r, &mut authorizer, run_limits)).into_u32();
+    call(|r| authorizer_drop(r, authorizer))
+        .into_unit()
+        .unwrap();
+    result
+}
+
+pub(super) const POLICIES: &str = r#"
+    resource("file1");
+    operation("read");
+    allow if right($resource, $operation), resource($resource), operation($operation);
+"#;
+
+#[test]
+fn public_key_hex_roundtrip() {
+    for algorithm in ALGORITHMS {
+        let keypair = new_keypair(algorithm);
+        let hex = public_key_hex(&keypair);
+        for hint in ALGORITHMS {
+            let parsed: Box<PublicKey> = call(|r| public_key_from_hex(r, &hex, hint))
+                .into_box()
+                .unwrap();
+            assert_eq!(*parsed, keypair.public());
+        }
+
+        // unprefixed keys still use the algorithm parameter
+        let unprefixed = keypair.public().to_bytes_hex();
+        let parsed: Box<PublicKey> = call(|r| public_key_from_hex(r, &unprefixed, algorithm))
+            .into_box()
+            .unwrap();
+        assert_eq!(*parsed, keypair.public());
+    }
+}
+
+#[test]
+fn private_key_hex_roundtrip() {
+    for algorithm in ALGORITHMS {
+        let keypair = new_keypair(algorithm);
+        let private_key: Box<PrivateKey> = call(|r| keypair_private_key(r, &keypair))
+            .into_box()
+            .unwrap();
+        let hex = call(|r| private_key_to_hex(r, &private_key))
+            .into_secret()
+            .unwrap();
+        assert_eq!(hex, private_key.to_prefixed_string());
+
+        let parsed: Box<PrivateKey> = call(|r| private_key_from_hex(r, &hex)).into_box().unwrap();
+        let rebuilt: Box<KeyPair> = call(|r| keypair_from_private_key(r, &parsed))
+            .into_box()
+            .unwrap();
+        assert_eq!(rebuilt.public(), keypair.public());
+    }
+}
+
+#[test]
+fn build_attenuate_authorize() {
+    for root_algorithm in ALGORITHMS {
+        let root = new_keypair(root_algorithm);
+        let biscuit = mint(&root, r#"right("file1", "read"); right("file1", "write");"#);
+        let biscuit = reload(&biscuit, &root);
+        assert_eq!(authorize(&biscuit, POLICIES), Ok(0));
+
+        let attenuated: Box<Biscuit> = call(|r| {
+            biscuit_append(
+                r,
+                &biscuit,
+                block_builder(r#"check if operation("write");"#),
+            )
+        })
+        .into_box()
+        .unwrap();
+        let attenuated = reload(&attenuated, &root);
+        assert!(authorize(&attenuated, POLICIES).is_err());
+    }
+}
+
+#[test]
+fn third_party_blocks() {
+    // every combination of root and third party algorithms
+    for root_algorithm in ALGORITHMS {
+        for third_party_algorithm in ALGORITHMS {
+            let root = new_keypair(root_algorithm);
+            let third_party = new_keypair(third_party_algorithm);
+            let biscuit = mint(
+                &root,
+                &format!(
+                    r#"right("file1", "read"); check if group("admin") trusting {};"#,
+                    public_key_hex(&third_party)
+                ),
+            );
+            assert!(authorize(&biscuit, POLICIES).is_err());
+
+            let request: Box<ThirdPartyRequest> =
+                call(|r| biscuit_third_party_request(r, &biscuit))
+                    .into_box()
+                    .unwrap();
+            let serialized = call(|r| third_party_request_to_base64(r, &request))
+                .into_string()
+                .unwrap();
+            call(|r| third_party_request_drop(r, request))
+                .into_unit()
+                .unwrap();
+
+            // the third party signs the block on its side
+            let request: Box<ThirdPartyRequest> =
+                call(|r| third_party_request_from_base64(r, &serialized))
+                    .into_box()
+                    .unwrap();
+            let private_key: Box<PrivateKey> = call(|r| keypair_private_key(r, &third_party))
+                .into_box()
+                .unwrap();
+            let block: Box<ThirdPartyBlock> = call(|r| {
+                third_party_request_create_block(
+                    r,
+                    request,
+                    &private_key,
+                    block_builder(r#"group("admin");"#),
+                )
+            })
+            .into_box()
+            .unwrap();
+
+            let third_party_public_key = public_key(&third_party);
+            let biscuit: Box<Biscuit> =
+                call(|r| biscuit_append_third_party(r, &biscuit, &third_party_public_key, block))
+                    .into_box()
+                    .unwrap();
+            let biscuit = reload(&biscuit, &root);
+            assert_eq!(authorize(&biscuit, POLICIES), Ok(0));
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Inspection of the authorizer
+
+use crate::builder::authorizer_builder::*;
+use crate::builder::biscuit_builder::*;
+use crate::builder::block_builder::*;
+use crate::crypto::SignatureAlgorithm;
+use crate::crypto::keypair::*;
+use crate::tests::call;
+use crate::token::authorizer::*;
+use crate::token::biscuit::*;
+use biscuit_auth::{Authorizer, AuthorizerBuilder, Biscuit, BiscuitBuilder, BlockBuilder, KeyPair};
+
+const LIMITS: &str = r#"{"max_time": 10000}"#;
+
+#[test]
+fn dump_world_json() {
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| {
+        biscuit_builder_add_code(
+            r,
+            &mut builder,
+            r#"user("alice"); admin($u) <- user($u), $u == "alice";"#,
+        )
+    })
+    .into_unit()
+    .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+    let mut block: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    call(|r| block_builder_add_code(r, &mut block, r#"check if operation("read");"#))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_append(r, &biscuit, block))
+        .into_box()
+        .unwrap();
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| {
+        authorizer_builder_add_code(
+            r,
+            &mut builder,
+            r#"operation("read"); allow if admin("alice"); deny if true;"#,
+        )
+    })
+    .into_unit()
+    .unwrap();
+    let mut authorizer: Box<Authorizer> = call(|r| authorizer_builder_build(r, builder, &biscuit))
+        .into_box()
+        .unwrap();
+    let run_limits = call(|r| authorizer_run_limits(r, 10_000))
+        .into_box()
+        .unwrap();
+    assert_eq!(
+        call(|r| authorizer_authorize(r, &mut authorizer, run_limits)).into_u32(),
+        Ok(0)
+    );
+
+    let json: serde_json::Value = serde_json::from_str(
+        &call(|r| authorizer_dump_json(r, &authorizer))
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "facts": [
+                { "origin": ["authority"], "facts": [r#"admin("alice")"#, r#"user("alice")"#] },
+                { "origin": ["authorizer"], "facts": [r#"operation("read")"#] },
+            ],
+            "rules": [
+                { "origin": "authority", "code": r#"admin($u) <- user($u), $u == "alice""# },
+            ],
+            "checks": [
+                { "origin": "block 1", "code": r#"check if operation("read")"# },
+            ],
+            "policies": [
+                { "origin": "authorizer", "code": r#"allow if admin("alice")"# },
+                { "origin": "authorizer", "code": "deny if true" },
+            ],
+        })
+    );
+}
+
+#[test]
+fn batch_authorization() {
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let other: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Secp256r1))
+        .into_box()
+        .unwrap();
+    let token = |root: &KeyPair, code: &str| -> Vec<u8> {
+        let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+        call(|r| biscuit_builder_add_code(r, &mut builder, code))
+            .into_unit()
+            .unwrap();
+        let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, root))
+            .into_box()
+            .unwrap();
+        call(|r| biscuit_to_bytes(r, &biscuit))
+            .into_bytes()
+            .unwrap()
+    };
+    let tokens = [
+        token(&root, r#"user("alice");"#),
+        token(&root, r#"user("bob");"#),
+        token(&other, r#"user("alice");"#),
+        b"not a token".to_vec(),
+        token(&root, r#"user("alice"); check if operation("write");"#),
+    ];
+    let mut buffer = Vec::new();
+    for token in &tokens {
+        buffer.extend((token.len() as u32).to_le_bytes());
+        buffer.extend(token);
+    }
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| {
+        authorizer_builder_add_code(
+            r,
+            &mut builder,
+            r#"operation("read"); deny if user("mallory"); allow if user("alice"); allow if user("bob");"#,
+        )
+    })
+    .into_unit()
+    .unwrap();
+    let run_limits = call(|r| authorizer_run_limits(r, 10_000))
+        .into_box()
+        .unwrap();
+    let root_public_key = root.public();
+
+    let outcomes = call(|r| authorize_batch(r, &builder, &run_limits, &buffer, &root_public_key))
+        .into_bytes()
+        .unwrap();
+    let outcomes: Vec<i32> = outcomes
+        .chunks_exact(4)
+        .map(|outcome| i32::from_le_bytes(outcome.try_into().unwrap()))
+        .collect();
+    assert_eq!(outcomes, vec![1, 2, -1, -1, -2]);
+
+    assert!(
+        call(|r| {
+            authorize_batch(
+                r,
+                &builder,
+                &run_limits,
+                &buffer[..buffer.len() - 1],
+                &root_public_key,
+            )
+        })
+        .into_bytes()
+        .is_err()
+    );
+    assert_eq!(
+        call(|r| authorize_batch(r, &builder, &run_limits, &[], &root_public_key)).into_bytes(),
+        Ok(Vec::new())
+    );
+}
+
+#[test]
+fn one_shot_authorization() {
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Secp256r1))
+        .into_box()
+        .unwrap();
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_add_code(r, &mut builder, r#"right("file1", "read");"#))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+    let token = call(|r| biscuit_to_base64(r, &biscuit))
+        .into_string()
+        .unwrap();
+    let public_key = root.public().to_string();
+
+    let code = r#"
+        resource({resource});
+        operation({operation});
+        allow if right($resource, $operation), resource($resource), operation($operation);
+    "#;
+    let authorize = |public_key: &str, params: &str| {
+        call(|r| biscuit_verify_and_authorize(r, &token, public_key, code, params, LIMITS))
+            .into_u32()
+    };
+    assert_eq!(
+        authorize(&public_key, r#"{"resource": "file1", "operation": "read"}"#),
+        Ok(0)
+    );
+
+    let error = authorize(
+        &public_key,
+        r#"{"resource": "file1", "operation": "write"}"#,
+    )
+    .unwrap_err();
+    assert!(error.starts_with(r#"{"FailedLogic":"#));
+    let error = authorize(&public_key, r#"{"resource": "file1", "operation": 1.5}"#).unwrap_err();
+    assert!(error.starts_with(r#"{"ConversionError":"#));
+    // parameters left unbound
+    let error = authorize(&public_key, "").unwrap_err();
+    assert!(error.starts_with(r#"{"Language":"#));
+
+    let other: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Secp256r1))
+        .into_box()
+        .unwrap();
+    let error = authorize(
+        &other.public().to_string(),
+        r#"{"resource": "file1", "operation": "read"}"#,
+    )
+    .unwrap_err();
+    assert!(error.starts_with(r#"{"Format":"#));
+}
+
+#[test]
+fn one_shot_run_limits() {
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_add_code(r, &mut builder, "n(1); n(2); n(3);"))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+    let token = call(|r| biscuit_to_base64(r, &biscuit))
+        .into_string()
+        .unwrap();
+    let public_key = root.public().to_string();
+
+    let code = "m($n) <- n($n); allow if m(3);";
+    let authorize = |limits: &str| {
+        call(|r| biscuit_verify_and_authorize(r, &token, &public_key, code, "", limits)).into_u32()
+    };
+    assert_eq!(authorize(""), Ok(0));
+    assert_eq!(
+        authorize(r#"{"max_iterations": 10, "max_time": 10000}"#),
+        Ok(0)
+    );
+
+    let error = authorize(r#"{"max_facts": 2, "max_time": 10000}"#).unwrap_err();
+    assert!(error.starts_with(r#"{"RunLimit":"#), "{error}");
+    for limits in [r#"{"max_time": "1s"}"#, r#"{"timeout": 10}"#, "[]"] {
+        let error = authorize(limits).unwrap_err();
+        assert!(error.starts_with(r#"{"ConversionError":"#), "{error}");
+    }
+}
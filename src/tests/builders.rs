@@ -0,0 +1,561 @@
+//! Builders filled element by element
+
+use crate::builder::authorizer_builder::*;
+use crate::builder::biscuit_builder::*;
+use crate::builder::block_builder::*;
+use crate::crypto::SignatureAlgorithm;
+use crate::crypto::keypair::*;
+use crate::tests::call;
+use crate::token::authorizer::*;
+use crate::token::biscuit::*;
+use biscuit_auth::{Authorizer, AuthorizerBuilder, Biscuit, BiscuitBuilder, BlockBuilder, KeyPair};
+
+#[test]
+fn biscuit_builder_elements() {
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    let third_party: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let third_party_public_key = third_party.public();
+
+    call(|r| biscuit_builder_add_fact(r, &mut builder, r#"user("alice")"#))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_rule(r, &mut builder, r#"admin($u) <- user($u), $u == "alice""#))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_check(r, &mut builder, r#"check if operation("read")"#))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_scope_authority(r, &mut builder))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_scope_previous(r, &mut builder))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_scope_public_key(r, &mut builder, &third_party_public_key))
+        .into_unit()
+        .unwrap();
+
+    // invalid elements are reported and leave the builder untouched
+    assert!(
+        call(|r| biscuit_builder_add_fact(r, &mut builder, "user($unbound)"))
+            .into_unit()
+            .is_err()
+    );
+    assert!(
+        call(|r| biscuit_builder_add_rule(r, &mut builder, "admin($u) <-"))
+            .into_unit()
+            .is_err()
+    );
+    assert!(
+        call(|r| biscuit_builder_add_check(r, &mut builder, "check user(\"alice\")"))
+            .into_unit()
+            .is_err()
+    );
+
+    let code = call(|r| biscuit_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    assert!(code.contains(r#"user("alice");"#));
+    assert!(code.contains(r#"admin($u) <- user($u), $u == "alice";"#));
+    assert!(code.contains(r#"check if operation("read");"#));
+
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+    assert!(
+        biscuit
+            .print()
+            .contains("scopes: [Authority, Previous, PublicKey(0)]")
+    );
+}
+
+#[test]
+fn block_builder_common_checks() {
+    let mut builder: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    call(|r| block_builder_check_expiration_date(r, &mut builder, 1_893_456_000))
+        .into_unit()
+        .unwrap();
+    call(|r| block_builder_check_resource(r, &mut builder, "file1"))
+        .into_unit()
+        .unwrap();
+    call(|r| block_builder_check_resource_prefix(r, &mut builder, "/public/"))
+        .into_unit()
+        .unwrap();
+    call(|r| block_builder_check_resource_suffix(r, &mut builder, ".txt"))
+        .into_unit()
+        .unwrap();
+    call(|r| block_builder_check_operation(r, &mut builder, "read"))
+        .into_unit()
+        .unwrap();
+
+    let code = call(|r| block_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    assert_eq!(
+        code,
+        r#"check if time($time), $time <= 2030-01-01T00:00:00Z;
+check if resource("file1");
+check if resource($resource), $resource.starts_with("/public/");
+check if resource($resource), $resource.ends_with(".txt");
+check if operation("read");
+"#
+    );
+}
+
+#[test]
+fn biscuit_builder_common_checks() {
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_check_expiration_date(r, &mut builder, 1_893_456_000))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_check_resource(r, &mut builder, "file1"))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_check_resource_prefix(r, &mut builder, "/public/"))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_check_resource_suffix(r, &mut builder, ".txt"))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_check_operation(r, &mut builder, "read"))
+        .into_unit()
+        .unwrap();
+
+    let code = call(|r| biscuit_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    for check in [
+        "check if time($time), $time <= 2030-01-01T00:00:00Z;",
+        r#"check if resource("file1");"#,
+        r#"check if resource($resource), $resource.starts_with("/public/");"#,
+        r#"check if resource($resource), $resource.ends_with(".txt");"#,
+        r#"check if operation("read");"#,
+    ] {
+        assert!(code.contains(check), "{check} missing from {code}");
+    }
+}
+
+#[test]
+fn expiration_date_out_of_range() {
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    let error = call(|r| biscuit_builder_check_expiration_date(r, &mut builder, u64::MAX))
+        .into_unit()
+        .unwrap_err();
+    assert!(error.contains("invalid date"), "{error}");
+    // the builder is left as it was
+    call(|r| biscuit_builder_check_operation(r, &mut builder, "read"))
+        .into_unit()
+        .unwrap();
+    let code = call(|r| biscuit_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    assert!(!code.contains("time"), "{code}");
+
+    let mut builder: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    assert!(
+        call(|r| block_builder_check_expiration_date(r, &mut builder, u64::MAX))
+            .into_unit()
+            .is_err()
+    );
+    call(|r| block_builder_check_operation(r, &mut builder, "read"))
+        .into_unit()
+        .unwrap();
+}
+
+#[test]
+fn block_contexts() {
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_set_context(r, &mut builder, "request-1"))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+
+    let mut block: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    call(|r| block_builder_set_context(r, &mut block, "request-2"))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_append(r, &biscuit, block))
+        .into_box()
+        .unwrap();
+    let mut block: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    call(|r| block_builder_set_context(r, &mut block, ""))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_append(r, &biscuit, block))
+        .into_box()
+        .unwrap();
+    let block: Box<BlockBuilder> = call(block_builder_new).into_box().unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_append(r, &biscuit, block))
+        .into_box()
+        .unwrap();
+
+    let contexts: Vec<_> = (0..5)
+        .map(|index| call(|r| biscuit_block_context(r, &biscuit, index)).into_option_string())
+        .collect();
+    assert_eq!(contexts[0], Ok(Some("request-1".to_string())));
+    assert_eq!(contexts[1], Ok(Some("request-2".to_string())));
+    // an empty context is told apart from a missing one
+    assert_eq!(contexts[2], Ok(Some(String::new())));
+    assert_eq!(contexts[3], Ok(None));
+    assert!(contexts[4].is_err());
+}
+
+#[test]
+fn root_key_id() {
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    for root_key_id in [None, Some(0), Some(42)] {
+        let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+        if let Some(id) = root_key_id {
+            call(|r| biscuit_builder_set_root_key_id(r, &mut builder, id))
+                .into_unit()
+                .unwrap();
+        }
+        let biscuit: Box<Biscuit> =
+            call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+                .into_box()
+                .unwrap();
+        assert_eq!(
+            call(|r| biscuit_root_key_id(r, &biscuit)).into_option_u32(),
+            Ok(root_key_id)
+        );
+    }
+}
+
+#[test]
+fn builders_from_json() {
+    let mut builder: Box<BiscuitBuilder> = call(|r| {
+        biscuit_builder_from_json(
+            r,
+            r#"{"facts": ["user(\"alice\")"], "checks": ["check if operation(\"read\")"]}"#,
+        )
+    })
+    .into_box()
+    .unwrap();
+    let other: Box<BiscuitBuilder> =
+        call(|r| biscuit_builder_from_json(r, r#"{"rules": ["admin($u) <- user($u)"]}"#))
+            .into_box()
+            .unwrap();
+    call(|r| biscuit_builder_merge(r, &mut builder, other))
+        .into_unit()
+        .unwrap();
+    let code = call(|r| biscuit_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    assert!(code.contains(r#"user("alice");"#));
+    assert!(code.contains("admin($u) <- user($u);"));
+    assert!(code.contains(r#"check if operation("read");"#));
+
+    // biscuits have no policies
+    let error = call(|r| biscuit_builder_from_json(r, r#"{"policies": ["allow if true"]}"#))
+        .into_box::<BiscuitBuilder>()
+        .err()
+        .unwrap();
+    assert!(error.starts_with(r#"{"Json":"unknown field `policies`"#));
+
+    let error = call(|r| biscuit_builder_from_json(r, r#"{"scopes": ["previous", "ed25519/00"]}"#))
+        .into_box::<BiscuitBuilder>()
+        .err()
+        .unwrap();
+    let error: serde_json::Value = serde_json::from_str(&error).unwrap();
+    assert_eq!(error["Items"][0]["section"], "scopes");
+    assert_eq!(error["Items"][0]["index"], 1);
+
+    let error = call(|r| {
+        authorizer_builder_from_json(
+            r,
+            r#"{"facts": ["ok(1)", "invalid("], "policies": ["allow if true", "allow true"]}"#,
+        )
+    })
+    .into_box::<AuthorizerBuilder>()
+    .err()
+    .unwrap();
+    let error: serde_json::Value = serde_json::from_str(&error).unwrap();
+    let locations: Vec<_> = error["Items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|item| {
+            (
+                item["section"].as_str().unwrap(),
+                item["index"].as_u64().unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(locations, vec![("facts", 1), ("policies", 1)]);
+
+    let mut authorizer_builder: Box<AuthorizerBuilder> =
+        call(|r| authorizer_builder_from_json(r, r#"{"facts": ["operation(\"read\")"]}"#))
+            .into_box()
+            .unwrap();
+    let policies: Box<AuthorizerBuilder> =
+        call(|r| authorizer_builder_from_json(r, r#"{"policies": ["allow if admin(\"alice\")"]}"#))
+            .into_box()
+            .unwrap();
+    call(|r| authorizer_builder_merge(r, &mut authorizer_builder, policies))
+        .into_unit()
+        .unwrap();
+
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+    let mut authorizer: Box<Authorizer> =
+        call(|r| authorizer_builder_build(r, authorizer_builder, &biscuit))
+            .into_box()
+            .unwrap();
+    let run_limits = call(|r| authorizer_run_limits(r, 10_000))
+        .into_box()
+        .unwrap();
+    assert_eq!(
+        call(|r| authorizer_authorize(r, &mut authorizer, run_limits)).into_u32(),
+        Ok(0)
+    );
+}
+
+#[test]
+fn merged_scopes() {
+    let third_party: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let third_party_public_key = third_party.public();
+
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    let mut other: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_add_fact(r, &mut other, r#"user("alice")"#))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_scope_previous(r, &mut other))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_scope_public_key(r, &mut other, &third_party_public_key))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_merge(r, &mut builder, other))
+        .into_unit()
+        .unwrap();
+
+    let root: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Ed25519))
+        .into_box()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+    let printed = biscuit.print();
+    assert!(printed.contains(r#"user("alice")"#), "{printed}");
+    assert!(
+        printed.contains("scopes: [Previous, PublicKey(0)]"),
+        "{printed}"
+    );
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    let json = format!(r#"{{"scopes": ["previous", "{third_party_public_key}"]}}"#);
+    let other: Box<AuthorizerBuilder> = call(|r| authorizer_builder_from_json(r, &json))
+        .into_box()
+        .unwrap();
+    call(|r| authorizer_builder_merge(r, &mut builder, other))
+        .into_unit()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(
+        &call(|r| authorizer_builder_to_json(r, &builder))
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json["scopes"],
+        serde_json::json!(["previous", third_party_public_key.to_string()])
+    );
+}
+
+#[test]
+fn builders_to_json() {
+    let third_party: Box<KeyPair> = call(|r| keypair_new(r, SignatureAlgorithm::Secp256r1))
+        .into_box()
+        .unwrap();
+    let third_party_public_key = third_party.public();
+
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| {
+        biscuit_builder_add_code(
+            r,
+            &mut builder,
+            r#"user("alice"); admin($u) <- user($u); check if operation("read");"#,
+        )
+    })
+    .into_unit()
+    .unwrap();
+    call(|r| biscuit_builder_add_scope_previous(r, &mut builder))
+        .into_unit()
+        .unwrap();
+    call(|r| biscuit_builder_add_scope_public_key(r, &mut builder, &third_party_public_key))
+        .into_unit()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(
+        &call(|r| biscuit_builder_to_json(r, &builder))
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "facts": [r#"user("alice")"#],
+            "rules": ["admin($u) <- user($u)"],
+            "checks": [r#"check if operation("read")"#],
+        })
+    );
+
+    // the dump is loaded back into the same builder
+    let loaded: Box<BiscuitBuilder> = call(|r| biscuit_builder_from_json(r, &json.to_string()))
+        .into_box()
+        .unwrap();
+    let reloaded: serde_json::Value = serde_json::from_str(
+        &call(|r| biscuit_builder_to_json(r, &loaded))
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reloaded, json);
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    let code = format!(
+        r#"time(2025-01-01T00:00:00Z);
+admin($u) <- user($u) trusting {third_party_public_key};
+check if time($t), $t < 2030-01-01T00:00:00Z;
+allow if admin("alice");
+deny if true;
+"#
+    );
+    call(|r| authorizer_builder_add_code(r, &mut builder, &code))
+        .into_unit()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(
+        &call(|r| authorizer_builder_to_json(r, &builder))
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "facts": ["time(2025-01-01T00:00:00Z)"],
+            "rules": [format!("admin($u) <- user($u) trusting {third_party_public_key}")],
+            "checks": ["check if time($t), $t < 2030-01-01T00:00:00Z"],
+            "policies": [r#"allow if admin("alice")"#, "deny if true"],
+            "scopes": [],
+        })
+    );
+
+    let loaded: Box<AuthorizerBuilder> =
+        call(|r| authorizer_builder_from_json(r, &json.to_string()))
+            .into_box()
+            .unwrap();
+    let reloaded: serde_json::Value = serde_json::from_str(
+        &call(|r| authorizer_builder_to_json(r, &loaded))
+            .into_string()
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reloaded, json);
+
+    let code = call(|r| authorizer_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    assert!(code.contains(r#"allow if admin("alice");"#));
+    assert!(code.contains("deny if true;"));
+}
+
+#[test]
+fn authorizer_builder_warnings() {
+    let lint = |builder: &AuthorizerBuilder| -> serde_json::Value {
+        serde_json::from_str(
+            &call(|r| authorizer_builder_lint(r, builder))
+                .into_string()
+                .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| {
+        authorizer_builder_add_code(
+            r,
+            &mut builder,
+            r#"allow if user("alice"); allow if true; deny if true;"#,
+        )
+    })
+    .into_unit()
+    .unwrap();
+    let warnings = lint(&builder);
+    assert_eq!(warnings.as_array().unwrap().len(), 1);
+    assert_eq!(warnings[0]["kind"], "unreachable_policy");
+    assert_eq!(warnings[0]["section"], "policies");
+    assert_eq!(warnings[0]["index"], 2);
+    // positions in the printed builder, one statement per line
+    let code = call(|r| authorizer_builder_to_string(r, &builder))
+        .into_string()
+        .unwrap();
+    assert_eq!(code.lines().nth(2), Some("deny if true;"));
+    assert_eq!(
+        warnings[0]["start"],
+        serde_json::json!({"line": 3, "column": 1, "offset": 39})
+    );
+    assert_eq!(warnings[0]["end"]["line"], 3);
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| authorizer_builder_add_code(r, &mut builder, r#"deny if user("mallory");"#))
+        .into_unit()
+        .unwrap();
+    let warnings = lint(&builder);
+    assert_eq!(warnings.as_array().unwrap().len(), 1);
+    assert_eq!(warnings[0]["kind"], "no_allow_policy");
+    assert_eq!(warnings[0]["index"], serde_json::Value::Null);
+    assert_eq!(warnings[0]["start"], serde_json::Value::Null);
+
+    // facts, rules and checks come before the policies in the printed builder
+    let mut builder: Box<AuthorizerBuilder> = call(|r| {
+        authorizer_builder_from_json(
+            r,
+            r#"{"facts": ["user(\"alice\")"], "checks": ["check if user($u)"],
+                "policies": ["deny if true", "allow if user(\"alice\")"]}"#,
+        )
+    })
+    .into_box()
+    .unwrap();
+    let warnings = lint(&builder);
+    assert_eq!(warnings.as_array().unwrap().len(), 1);
+    assert_eq!(warnings[0]["index"], 1);
+    assert_eq!(warnings[0]["start"]["line"], 4);
+    assert_eq!(warnings[0]["start"]["column"], 1);
+    // the printed code parses even when it was not written as datalog source
+    call(|r| authorizer_builder_add_code(r, &mut builder, r#"operation("read");"#))
+        .into_unit()
+        .unwrap();
+    assert_eq!(lint(&builder)[0]["start"]["line"], 5);
+
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| {
+        authorizer_builder_add_code(
+            r,
+            &mut builder,
+            r#"admin($u) <- user($u); allow if admin("alice"); deny if true;"#,
+        )
+    })
+    .into_unit()
+    .unwrap();
+    assert_eq!(lint(&builder), serde_json::json!([]));
+}
@@ -0,0 +1,112 @@
+//! Datalog tooling working on source code
+
+use crate::datalog::*;
+use crate::tests::call;
+
+fn validate(code: &str, kind: DatalogKind) -> Result<(), serde_json::Value> {
+    call(|r| datalog_validate(r, code, kind))
+        .into_unit()
+        .map_err(|error| serde_json::from_str(&error).unwrap())
+}
+
+/// (line, column) of the start and end of every error
+fn spans(errors: &serde_json::Value) -> Vec<((u64, u64), (u64, u64))> {
+    let position = |position: &serde_json::Value| {
+        (
+            position["line"].as_u64().unwrap(),
+            position["column"].as_u64().unwrap(),
+        )
+    };
+    errors
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|error| (position(&error["start"]), position(&error["end"])))
+        .collect()
+}
+
+#[test]
+fn validate_sources() {
+    let block = "user(\"alice\");\ncheck if operation(\"read\");\n";
+    assert_eq!(validate(block, DatalogKind::Block), Ok(()));
+    assert_eq!(validate(block, DatalogKind::Authorizer), Ok(()));
+
+    // policies are only allowed in authorizers
+    let authorizer = "user(\"alice\");\n  allow if user(\"alice\");\n";
+    assert_eq!(validate(authorizer, DatalogKind::Authorizer), Ok(()));
+    let errors = validate(authorizer, DatalogKind::Block).unwrap_err();
+    assert_eq!(spans(&errors), vec![((2, 3), (2, 25))]);
+
+    // every invalid statement is reported
+    let errors = validate(
+        "user(\"alice\");\nadmin($u) <- ;\nright(\"é\", $r);\n",
+        DatalogKind::Authorizer,
+    )
+    .unwrap_err();
+    let spans = spans(&errors);
+    assert_eq!(spans.len(), 2);
+    assert_eq!(spans[0].0.0, 2);
+    assert_eq!(spans[1].0.0, 3);
+}
+
+#[test]
+fn validate_elements() {
+    assert_eq!(validate("user(\"alice\")", DatalogKind::Fact), Ok(()));
+    assert_eq!(validate("admin($u) <- user($u)", DatalogKind::Rule), Ok(()));
+    assert_eq!(
+        validate("check if operation(\"read\")", DatalogKind::Check),
+        Ok(())
+    );
+    assert_eq!(validate("allow if true", DatalogKind::Policy), Ok(()));
+
+    assert!(validate("check if operation(\"read\")", DatalogKind::Policy).is_err());
+    let errors = validate("admin($u) <- user($v)", DatalogKind::Rule).unwrap_err();
+    assert_eq!(errors.as_array().unwrap().len(), 1);
+    let errors = validate("user(\"alice\") trailing", DatalogKind::Fact).unwrap_err();
+    assert_eq!(spans(&errors), vec![((1, 15), (1, 23))]);
+}
+
+#[test]
+fn format_sources() {
+    let code = r#"
+        // policies keep their order
+        allow if   user($u),admin( $u ) ;
+        user("bob");user("alice");
+        check if operation("read")  ;
+        /* facts and rules are sorted */
+        admin($u)<-user($u),$u=="alice";
+        deny if true
+    "#;
+    let formatted = call(|r| datalog_format(r, code)).into_string().unwrap();
+    assert_eq!(
+        formatted,
+        r#"user("alice");
+user("bob");
+
+admin($u) <- user($u), $u == "alice";
+
+check if operation("read");
+
+allow if user($u), admin($u);
+deny if true;
+"#
+    );
+    // the canonical form is stable
+    assert_eq!(
+        call(|r| datalog_format(r, &formatted)).into_string(),
+        Ok(formatted.clone())
+    );
+
+    let formatted = call(|r| datalog_format(r, "trusting previious ;"))
+        .into_string()
+        .unwrap_err();
+    assert_eq!(
+        spans(&serde_json::from_str(&formatted).unwrap()),
+        vec![((1, 1), (1, 20))]
+    );
+    assert_eq!(
+        call(|r| datalog_format(r, "trusting previous, authority; right(\"file1\");"))
+            .into_string(),
+        Ok("trusting previous, authority;\n\nright(\"file1\");\n".to_string())
+    );
+}
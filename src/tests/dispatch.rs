@@ -0,0 +1,210 @@
+use crate::dispatch::*;
+use crate::tests::call;
+use serde_json::{Value, json};
+
+fn send(request: Value) -> Value {
+    let response = call(|r| dispatch(r, &request.to_string()))
+        .into_secret()
+        .unwrap();
+    let response: Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(response["id"], request["id"]);
+    response
+}
+
+fn ok(method: &str, params: Value) -> Value {
+    let response = send(json!({ "id": 1, "method": method, "params": params }));
+    assert!(response.get("error").is_none(), "{method}: {response}");
+    response["result"].clone()
+}
+
+fn err(method: &str, params: Value) -> Value {
+    let response = send(json!({ "id": "request", "method": method, "params": params }));
+    assert!(response.get("result").is_none(), "{method}: {response}");
+    response["error"].clone()
+}
+
+#[test]
+fn dispatch_token_flow() {
+    let root = ok("keypair_new", json!({ "signature_algorithm": "secp256r1" }));
+    let public_key = ok("keypair_public_key", json!({ "keypair": root }));
+    let public_key_hex = ok("public_key_to_hex", json!({ "public_key": public_key }));
+    assert!(public_key_hex.as_str().unwrap().starts_with("secp256r1/"));
+
+    let builder = ok("biscuit_builder_new", json!({}));
+    ok(
+        "biscuit_builder_add_code",
+        json!({ "builder": builder, "code": r#"right("file1", "read");"# }),
+    );
+    let biscuit = ok(
+        "biscuit_builder_build_with_key_pair",
+        json!({ "builder": builder, "root_keypair": root }),
+    );
+    // the builder was consumed by the build
+    assert_eq!(
+        err("biscuit_builder_drop", json!({ "builder": builder })),
+        json!({ "Dispatch": format!("unknown handle {builder}") })
+    );
+
+    let token = ok("biscuit_to_base64", json!({ "biscuit": biscuit }));
+    let bytes = ok("biscuit_to_bytes", json!({ "biscuit": biscuit }));
+    let reloaded = ok(
+        "biscuit_from_bytes",
+        json!({ "data": bytes, "root_public_key": public_key }),
+    );
+    assert_eq!(
+        ok("biscuit_root_key_id", json!({ "biscuit": reloaded })),
+        Value::Null
+    );
+    assert_eq!(
+        ok(
+            "biscuit_block_context",
+            json!({ "biscuit": reloaded, "index": 0 })
+        ),
+        Value::Null
+    );
+
+    let authorizer_builder = ok("authorizer_builder_new", json!({}));
+    ok(
+        "authorizer_builder_add_code",
+        json!({
+            "builder": authorizer_builder,
+            "code": r#"resource("file1"); allow if right($r, "read"), resource($r);"#,
+        }),
+    );
+    let authorizer = ok(
+        "authorizer_builder_build",
+        json!({ "builder": authorizer_builder, "token": reloaded }),
+    );
+    let run_limits = ok("authorizer_run_limits", json!({ "max_time": 10_000 }));
+    assert_eq!(
+        ok(
+            "authorizer_authorize",
+            json!({ "authorizer": authorizer, "run_limits": run_limits }),
+        ),
+        json!(0)
+    );
+    let world = ok("authorizer_dump_json", json!({ "authorizer": authorizer }));
+    assert_eq!(world["facts"][0]["origin"], json!(["authority"]));
+
+    assert_eq!(
+        ok(
+            "biscuit_verify_and_authorize",
+            json!({
+                "token": token,
+                "public_key": public_key_hex,
+                "authorizer_code": r#"allow if right("file1", "read");"#,
+                "params_json": "",
+                "limits_json": r#"{"max_time": 10000}"#,
+            }),
+        ),
+        json!(0)
+    );
+
+    for (method, name, handle) in [
+        ("authorizer_drop", "authorizer", authorizer),
+        ("biscuit_drop", "biscuit", biscuit),
+        ("biscuit_drop", "biscuit", reloaded),
+        ("keypair_drop", "keypair", root),
+    ] {
+        ok(method, json!({ name: handle }));
+    }
+}
+
+#[test]
+fn dispatch_errors() {
+    assert_eq!(
+        send(json!({ "id": 7, "method": "unknown" }))["error"],
+        json!({ "Dispatch": "unknown method unknown" })
+    );
+    assert!(
+        send(json!({ "id": null, "method": "keypair_new" }))["error"]["Dispatch"]
+            .as_str()
+            .unwrap()
+            .contains("missing parameter signature_algorithm")
+    );
+
+    let builder = ok("block_builder_new", json!({}));
+    // handles are typed
+    assert!(
+        err("biscuit_builder_to_string", json!({ "builder": builder }))["Dispatch"]
+            .as_str()
+            .unwrap()
+            .starts_with(&format!("handle {builder} is not a"))
+    );
+    let other = ok("biscuit_builder_new", json!({}));
+    assert_eq!(
+        err(
+            "biscuit_builder_merge",
+            json!({ "builder": other, "other": other }),
+        ),
+        json!({ "Dispatch": format!("handle {other} passed twice") })
+    );
+
+    // errors of the exports are forwarded as they are
+    let error = err(
+        "block_builder_add_code",
+        json!({ "builder": builder, "code": "invalid(" }),
+    );
+    assert!(error.get("Language").is_some(), "{error}");
+    // a failed add_code leaves the builder unusable, its handle is released
+    assert!(
+        err("block_builder_drop", json!({ "builder": builder }))["Dispatch"]
+            .as_str()
+            .unwrap()
+            .starts_with("unknown handle")
+    );
+
+    // add_fact validates the fact first, the builder keeps its handle on error
+    let error = err(
+        "biscuit_builder_add_fact",
+        json!({ "builder": other, "fact": "right(" }),
+    );
+    assert!(error.get("Language").is_some(), "{error}");
+    ok(
+        "biscuit_builder_add_fact",
+        json!({ "builder": other, "fact": r#"right("file1", "read")"# }),
+    );
+    assert!(
+        ok("biscuit_builder_to_string", json!({ "builder": other }))
+            .as_str()
+            .unwrap()
+            .contains(r#"right("file1", "read");"#)
+    );
+
+    assert!(
+        err(
+            "datalog_validate",
+            json!({ "code": "fact(", "kind": "block" })
+        )
+        .is_array()
+    );
+    // byte arrays are hex strings
+    let keypair = ok("keypair_new", json!({ "signature_algorithm": "ed25519" }));
+    let signature = ok(
+        "keypair_sign",
+        json!({ "keypair": keypair, "data": "0001" }),
+    );
+    assert_eq!(signature.as_str().unwrap().len(), 128);
+    assert!(
+        err("keypair_sign", json!({ "keypair": keypair, "data": "0" }))["Dispatch"].is_string()
+    );
+    ok("keypair_drop", json!({ "keypair": keypair }));
+    ok("biscuit_builder_drop", json!({ "builder": other }));
+}
+
+#[test]
+fn dispatch_private_key() {
+    let public_key_hex = |keypair: &Value| {
+        let public_key = ok("keypair_public_key", json!({ "keypair": keypair }));
+        ok("public_key_to_hex", json!({ "public_key": public_key }))
+    };
+    let root = ok("keypair_new", json!({ "signature_algorithm": "ed25519" }));
+    let private_key = ok("keypair_private_key", json!({ "keypair": root }));
+    // `send` releases every response with secret_string_free, this one holds the key
+    let hex = ok("private_key_to_hex", json!({ "private_key": private_key }));
+    assert!(hex.as_str().unwrap().starts_with("ed25519-private/"));
+
+    let parsed = ok("private_key_from_hex", json!({ "data": hex }));
+    let rebuilt = ok("keypair_from_private_key", json!({ "private_key": parsed }));
+    assert_eq!(public_key_hex(&rebuilt), public_key_hex(&root));
+}
@@ -0,0 +1,61 @@
+//! Extern functions implemented by the host
+
+use crate::builder::authorizer_builder::*;
+use crate::builder::extern_func::*;
+use crate::tests::call;
+use crate::token::authorizer::*;
+use crate::wasm_result::{ResultKind, WasmResult};
+use biscuit_auth::{Authorizer, AuthorizerBuilder, Biscuit, KeyPair};
+use serde_json::{Value, json};
+
+/// Host side of the extern functions: `add` sums integers, `first` returns its first argument
+#[unsafe(no_mangle)]
+pub extern "C" fn extern_func_call(
+    name_ptr: *const u8,
+    name_len: usize,
+    args_ptr: *const u8,
+    args_len: usize,
+    result: &mut WasmResult,
+) {
+    let name = unsafe { std::slice::from_raw_parts(name_ptr, name_len) };
+    let args = unsafe { std::slice::from_raw_parts(args_ptr, args_len) };
+    let args: Vec<Value> = serde_json::from_slice(args).unwrap();
+    match name {
+        b"add" => {
+            let sum: i64 = args.iter().map(|arg| arg.as_i64().unwrap()).sum();
+            result.fill_as_host(json!(sum).to_string().into_bytes(), ResultKind::Ok);
+        }
+        b"first" => result.fill_as_host(args[0].to_string().into_bytes(), ResultKind::Ok),
+        _ => result.fill_as_host(b"unknown function".to_vec(), ResultKind::ErrBiscuit),
+    }
+}
+
+fn authorize(code: &str) -> Result<u32, String> {
+    let mut builder: Box<AuthorizerBuilder> = call(authorizer_builder_new).into_box().unwrap();
+    call(|r| authorizer_builder_add_code(r, &mut builder, code))
+        .into_unit()
+        .unwrap();
+    for name in ["add", "first", "missing"] {
+        call(|r| authorizer_builder_register_extern_func(r, &mut builder, name))
+            .into_unit()
+            .unwrap();
+    }
+    let biscuit = Biscuit::builder().build(&KeyPair::new()).unwrap();
+    let mut authorizer: Box<Authorizer> = call(|r| authorizer_builder_build(r, builder, &biscuit))
+        .into_box()
+        .unwrap();
+    let run_limits = call(|r| authorizer_run_limits(r, 10_000))
+        .into_box()
+        .unwrap();
+    call(|r| authorizer_authorize(r, &mut authorizer, run_limits)).into_u32()
+}
+
+#[test]
+fn host_extern_funcs() {
+    assert_eq!(authorize("allow if 1.extern::add(2) == 3;"), Ok(0));
+    assert_eq!(
+        authorize(r#"allow if {"a": [1, true]}.extern::first() == {"a": [1, true]};"#),
+        Ok(0)
+    );
+    assert!(authorize("allow if 1.extern::missing();").is_err());
+}
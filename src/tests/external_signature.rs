@@ -0,0 +1,75 @@
+//! Signing the authority block with an external signer
+
+use crate::builder::biscuit_builder::*;
+use crate::builder::external_signature::*;
+use crate::crypto::SignatureAlgorithm;
+use crate::crypto::keypair::*;
+use crate::tests::algorithms::{ALGORITHMS, POLICIES, authorize, new_keypair, public_key, reload};
+use crate::tests::call;
+use biscuit_auth::{Biscuit, BiscuitBuilder, PublicKey};
+
+fn prepare_external_signature(root_public_key: &PublicKey) -> (Box<UnsignedBiscuit>, Vec<u8>) {
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_add_code(r, &mut builder, r#"right("file1", "read");"#))
+        .into_unit()
+        .unwrap();
+    let unsigned: Box<UnsignedBiscuit> =
+        call(|r| biscuit_builder_prepare_external_signature(r, builder, root_public_key))
+            .into_box()
+            .unwrap();
+    let payload = call(|r| unsigned_biscuit_signature_payload(r, &unsigned))
+        .into_bytes()
+        .unwrap();
+    (unsigned, payload)
+}
+
+#[test]
+fn external_signature() {
+    for algorithm in ALGORITHMS {
+        let root = new_keypair(algorithm);
+        let root_public_key = public_key(&root);
+
+        // a signature from another key is rejected
+        let (unsigned, payload) = prepare_external_signature(&root_public_key);
+        let other = new_keypair(algorithm);
+        let signature = call(|r| keypair_sign(r, &other, &payload))
+            .into_bytes()
+            .unwrap();
+        assert!(
+            call(|r| unsigned_biscuit_finish(r, unsigned, &signature))
+                .into_box::<Biscuit>()
+                .is_err()
+        );
+
+        let (unsigned, payload) = prepare_external_signature(&root_public_key);
+        let signature = call(|r| keypair_sign(r, &root, &payload))
+            .into_bytes()
+            .unwrap();
+        let biscuit: Box<Biscuit> = call(|r| unsigned_biscuit_finish(r, unsigned, &signature))
+            .into_box()
+            .unwrap();
+        let biscuit = reload(&biscuit, &root);
+        assert_eq!(authorize(&biscuit, POLICIES), Ok(0));
+    }
+}
+
+/// The payload layout is copied from biscuit-auth: Ed25519 signatures are deterministic,
+/// so signing the rebuilt payload must give the signature biscuit-auth computed itself
+#[test]
+fn payload_matches_biscuit_auth() {
+    let root = new_keypair(SignatureAlgorithm::Ed25519);
+    let mut builder: Box<BiscuitBuilder> = call(biscuit_builder_new).into_box().unwrap();
+    call(|r| biscuit_builder_add_code(r, &mut builder, r#"right("file1", "read");"#))
+        .into_unit()
+        .unwrap();
+    let biscuit: Box<Biscuit> = call(|r| biscuit_builder_build_with_key_pair(r, builder, &root))
+        .into_box()
+        .unwrap();
+
+    let authority = biscuit.container().to_proto().authority;
+    let payload = authority_signature_payload(&authority);
+    let signature = call(|r| keypair_sign(r, &root, &payload))
+        .into_bytes()
+        .unwrap();
+    assert_eq!(signature, authority.signature);
+}
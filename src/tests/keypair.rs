@@ -0,0 +1,54 @@
+//! Deterministic keypairs derived from a seed or a passphrase
+
+use crate::crypto::keypair::*;
+use crate::tests::algorithms::{ALGORITHMS, POLICIES, authorize, mint, reload};
+use crate::tests::call;
+use biscuit_auth::KeyPair;
+
+#[test]
+fn keypair_derivation() {
+    for algorithm in ALGORITHMS {
+        let seed = [7u8; 32];
+        let first: Box<KeyPair> = call(|r| keypair_from_seed(r, algorithm, &seed))
+            .into_box()
+            .unwrap();
+        let second: Box<KeyPair> = call(|r| keypair_from_seed(r, algorithm, &seed))
+            .into_box()
+            .unwrap();
+        assert_eq!(first.public(), second.public());
+        let other: Box<KeyPair> = call(|r| keypair_from_seed(r, algorithm, &[8u8; 32]))
+            .into_box()
+            .unwrap();
+        assert_ne!(first.public(), other.public());
+
+        let first: Box<KeyPair> = call(|r| keypair_from_passphrase(r, algorithm, "dev", b"salt"))
+            .into_box()
+            .unwrap();
+        let second: Box<KeyPair> = call(|r| keypair_from_passphrase(r, algorithm, "dev", b"salt"))
+            .into_box()
+            .unwrap();
+        let salted: Box<KeyPair> =
+            call(|r| keypair_from_passphrase(r, algorithm, "dev", b"pepper"))
+                .into_box()
+                .unwrap();
+        assert_eq!(first.public(), second.public());
+        assert_ne!(first.public(), salted.public());
+
+        let biscuit = mint(&first, r#"right("file1", "read");"#);
+        let biscuit = reload(&biscuit, &second);
+        assert_eq!(authorize(&biscuit, POLICIES), Ok(0));
+    }
+}
+
+#[test]
+fn keypair_derivation_errors() {
+    for algorithm in ALGORITHMS {
+        for seed in [&[7u8; 16][..], &[7u8; 33][..], &[][..]] {
+            assert!(
+                call(|r| keypair_from_seed(r, algorithm, seed))
+                    .into_box::<KeyPair>()
+                    .is_err()
+            );
+        }
+    }
+}
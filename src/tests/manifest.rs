@@ -0,0 +1,47 @@
+use crate::manifest::*;
+
+fn export(name: &str) -> &'static Export {
+    exports()
+        .into_iter()
+        .find(|export| export.name == name)
+        .unwrap_or_else(|| panic!("{name} is not in the manifest"))
+}
+
+#[test]
+fn manifest_records_signatures() {
+    let build = export("biscuit_builder_build_with_key_pair");
+    let params: Vec<_> = build
+        .params
+        .iter()
+        .map(|param| serde_json::to_value(param).unwrap())
+        .collect();
+    assert_eq!(
+        params,
+        [
+            serde_json::json!({ "name": "builder", "type": "opaque", "type_name": "BiscuitBuilder", "ownership": "consumed" }),
+            serde_json::json!({ "name": "root_keypair", "type": "opaque", "type_name": "KeyPair", "ownership": "borrowed" }),
+        ]
+    );
+    assert!(matches!(build.result.ty, Type::Opaque("Biscuit")));
+    assert!(build.result.fallible && !build.result.optional);
+
+    let root_key_id = export("biscuit_root_key_id").result.ty;
+    assert!(matches!(root_key_id, Type::U32));
+    assert!(export("biscuit_root_key_id").result.optional);
+    assert!(matches!(
+        export("private_key_to_hex").result.ty,
+        Type::Secret
+    ));
+    assert!(matches!(
+        export("keypair_from_seed").params[0].ty,
+        Type::Enum("SignatureAlgorithm")
+    ));
+}
+
+#[cfg(all(feature = "extern-funcs", feature = "dispatch"))]
+#[test]
+fn abi_files_are_up_to_date() {
+    // regenerated with `cargo run --example manifest --features manifest,extern-funcs,dispatch`
+    assert_eq!(to_json(), include_str!("../../abi/biscuit_wasm_shim.json"));
+    assert_eq!(to_c_header(), include_str!("../../abi/biscuit_wasm_shim.h"));
+}
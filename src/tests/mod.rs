@@ -0,0 +1,27 @@
+//! Native tests calling the exports the way the host does, through a return area
+
+mod algorithms;
+mod authorizer;
+mod builders;
+mod datalog;
+#[cfg(feature = "dispatch")]
+mod dispatch;
+#[cfg(feature = "extern-funcs")]
+mod extern_funcs;
+mod external_signature;
+mod keypair;
+#[cfg(feature = "manifest")]
+mod manifest;
+#[cfg(any(feature = "print", feature = "catch-panic"))]
+mod panic;
+mod term_json;
+mod version;
+
+use crate::wasm_result::WasmResult;
+
+/// Calls an export with a fresh return area and returns it
+pub(crate) fn call(export: impl FnOnce(&mut WasmResult)) -> WasmResult {
+    let mut ret = WasmResult::new();
+    export(&mut ret);
+    ret
+}
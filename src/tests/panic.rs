@@ -0,0 +1,69 @@
+//! Panics inside exports, reported through the `print` import or returned as `ErrPanic`
+
+#[cfg(feature = "catch-panic")]
+use crate::panic::*;
+#[cfg(feature = "catch-panic")]
+use crate::tests::call;
+#[cfg(feature = "catch-panic")]
+use crate::wasm_result::WasmResult;
+#[cfg(feature = "print")]
+use std::sync::Mutex;
+
+#[cfg(feature = "print")]
+static PRINTED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Host side of `print`
+#[cfg(feature = "print")]
+#[unsafe(no_mangle)]
+pub extern "C" fn print(ptr: *const u8, len: usize) {
+    let message = unsafe { std::slice::from_raw_parts(ptr, len) };
+    let message = String::from_utf8(message.to_vec()).unwrap();
+    PRINTED.lock().unwrap().push(message);
+}
+
+// export going through the `catch-panic` path of `wasm_export!`, left out of the manifest
+#[cfg(feature = "catch-panic")]
+crate::wasm_export!(
+    fn panicking_export(value: u32) -> Result<u32, biscuit_auth::error::Token> {
+        if value == 0 {
+            panic!("zero is not accepted");
+        }
+        Ok(value)
+    }
+);
+
+#[cfg(feature = "catch-panic")]
+#[test]
+fn panics_are_returned_as_errors() {
+    install_hook();
+    let mut ret = WasmResult::new();
+    catch(&mut ret, || -> u32 { panic!("broken invariant") });
+    let message = ret.into_u32().unwrap_err();
+    assert!(
+        message.starts_with("panicked at src/tests/panic.rs:"),
+        "{message}"
+    );
+    assert!(message.ends_with("broken invariant"), "{message}");
+
+    #[cfg(feature = "print")]
+    assert!(PRINTED.lock().unwrap().contains(&message));
+
+    // the next calls are not affected
+    let mut ret = WasmResult::new();
+    catch(&mut ret, || 7u32);
+    assert_eq!(ret.into_u32(), Ok(7));
+}
+
+#[cfg(feature = "catch-panic")]
+#[test]
+fn export_panics_are_returned_as_errors() {
+    let message = call(|r| panicking_export(r, 0)).into_u32().unwrap_err();
+    // the message of ErrPanic is not JSON, unlike the one of ErrBiscuit
+    assert!(
+        message.starts_with("panicked at src/tests/panic.rs:"),
+        "{message}"
+    );
+    assert!(message.ends_with("zero is not accepted"), "{message}");
+
+    assert_eq!(call(|r| panicking_export(r, 7)).into_u32(), Ok(7));
+}
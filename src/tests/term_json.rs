@@ -0,0 +1,35 @@
+//! Conversion of datalog terms from and to JSON
+
+use crate::term_json::*;
+use biscuit_auth::builder::{MapKey, Term};
+use serde_json::json;
+
+#[test]
+fn terms_json_roundtrip() {
+    let terms = [
+        (Term::Integer(-3), json!(-3)),
+        (Term::Str("a".to_string()), json!("a")),
+        (Term::Bool(true), json!(true)),
+        (Term::Null, json!(null)),
+        (Term::Date(1_700_000_000), json!({ "date": 1_700_000_000 })),
+        (Term::Bytes(vec![0, 255]), json!({ "bytes": "00ff" })),
+        (
+            Term::Array(vec![Term::Integer(1), Term::Str("b".to_string())]),
+            json!([1, "b"]),
+        ),
+        (
+            Term::Set([Term::Integer(1), Term::Integer(2)].into()),
+            json!({ "set": [1, 2] }),
+        ),
+        (
+            Term::Map([(MapKey::Str("k".to_string()), Term::Integer(1))].into()),
+            json!({ "map": [["k", 1]] }),
+        ),
+    ];
+    for (term, value) in terms {
+        assert_eq!(term_to_json(&term), Ok(value.clone()));
+        assert_eq!(term_from_json(value), Ok(term));
+    }
+    assert!(term_from_json(json!({ "date": 1, "set": [] })).is_err());
+    assert!(term_from_json(json!(1.5)).is_err());
+}
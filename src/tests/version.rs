@@ -0,0 +1,36 @@
+use crate::tests::call;
+use crate::version::*;
+use serde_json::{Value, json};
+
+#[test]
+fn versions() {
+    assert_eq!(
+        call(shim_version).into_string().unwrap(),
+        env!("CARGO_PKG_VERSION")
+    );
+    assert_eq!(call(abi_version).into_u32().unwrap(), ABI_VERSION);
+
+    // the version resolved by cargo, from the lock file of the workspace
+    let lock = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.lock")).unwrap();
+    let resolved = lock
+        .split("[[package]]")
+        .find(|package| package.contains("name = \"biscuit-auth\"\n"))
+        .and_then(|package| package.split("version = \"").nth(1))
+        .and_then(|version| version.split('"').next())
+        .unwrap();
+    assert_eq!(call(biscuit_auth_version).into_string().unwrap(), resolved);
+}
+
+#[test]
+fn features() {
+    let features: Value =
+        serde_json::from_str(&call(shim_features).into_string().unwrap()).unwrap();
+    assert_eq!(features["algorithms"], json!(["ed25519", "secp256r1"]));
+    assert_eq!(features["datalog_versions"][3], "3.3");
+    let enabled = features["features"].as_array().unwrap();
+    assert_eq!(
+        enabled.contains(&json!("extern-funcs")),
+        cfg!(feature = "extern-funcs")
+    );
+    assert_eq!(enabled.contains(&json!("print")), cfg!(feature = "print"));
+}
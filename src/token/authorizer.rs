@@ -1,7 +1,9 @@
+use crate::token::world::AuthorizerWorldJson;
 use crate::wasm_export;
 use crate::wasm_result::WasmResult;
-use biscuit_auth::Authorizer;
 use biscuit_auth::datalog::RunLimits;
+use biscuit_auth::error::{Format, Token};
+use biscuit_auth::{Authorizer, AuthorizerBuilder, Biscuit, PublicKey};
 use std::time::Duration;
 
 // create a new authorizer builder
@@ -59,6 +61,118 @@ wasm_export!(
     }
 );
 
+// dump the world as JSON, for debugging tools
+// Input:
+// authorizer: a pointer to the authorizer allocated in the wasm memory
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the JSON string allocated in the wasm memory:
+// {
+//   "facts": [{ "origin": ["authority", "block 1", ...], "facts": [..] }],
+//   "rules": [{ "origin": "authorizer", "code": ".." }],
+//   "checks": [{ "origin": "block 2", "code": ".." }],
+//   "policies": [{ "origin": "authorizer", "code": ".." }]
+// }
+// origins are `authority`, `block N` or `authorizer`, facts include the ones generated by
+// the rules once the authorizer ran
+// data_len is the length of the JSON string
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn authorizer_dump_json(
+        authorizer: &Authorizer,
+    ) -> Result<String, biscuit_auth::error::Format> {
+        AuthorizerWorldJson::from_authorizer(authorizer)?.to_json()
+    }
+);
+
+/// Outcome of the authorization of one token of a batch, negative values are errors
+#[repr(i32)]
+enum BatchOutcome {
+    /// the token could not be parsed or its signatures are invalid
+    InvalidToken = -1,
+    /// a deny policy matched, a check failed or no policy matched
+    Unauthorized = -2,
+    /// the run limits were reached
+    RunLimit = -3,
+    /// any other error, e.g. an expression that failed to evaluate
+    Error = -4,
+}
+
+/// Parses and authorizes one token of a batch, returns the index of the matched allow policy
+fn authorize_one(
+    builder: &AuthorizerBuilder,
+    run_limits: &RunLimits,
+    token: &[u8],
+    root_public_key: &PublicKey,
+) -> i32 {
+    let outcome = Biscuit::from(token, *root_public_key)
+        .and_then(|biscuit| builder.clone().build(&biscuit))
+        .and_then(|mut authorizer| authorizer.authorize_with_limits(run_limits.clone()));
+    let error = match outcome {
+        Ok(policy) => return policy as i32,
+        Err(error) => error,
+    };
+    let outcome = match error {
+        Token::Format(_) | Token::Base64(_) | Token::AppendOnSealed | Token::AlreadySealed => {
+            BatchOutcome::InvalidToken
+        }
+        Token::FailedLogic(_) => BatchOutcome::Unauthorized,
+        Token::RunLimit(_) => BatchOutcome::RunLimit,
+        _ => BatchOutcome::Error,
+    };
+    outcome as i32
+}
+
+// authorize many tokens with the same authorizer in one call
+// Input:
+// builder: a pointer to the authorizer builder allocated in the wasm memory, reused for every token
+// run_limits: a pointer to the run limits allocated in the wasm memory, applied to every token
+// tokens {ptr, len}: the serialized tokens, each one prefixed by its length as a little endian u32
+// root_public_key: a pointer to the root public key allocated in the wasm memory
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the outcomes allocated in the wasm memory, one little endian i32 per
+// token, in order: the index of the matched allow policy, or a negative error code
+// -1: invalid token (parsing, signature), -2: unauthorized (deny policy, failed check or
+// no matching policy), -3: run limits reached, -4: other error
+// data_len is the length of the outcomes in bytes, 4 times the number of tokens
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// the tokens buffer is truncated
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn authorize_batch(
+        builder: &AuthorizerBuilder,
+        run_limits: &RunLimits,
+        tokens: &[u8],
+        root_public_key: &PublicKey,
+    ) -> Result<Vec<u8>, Format> {
+        let truncated = || Format::DeserializationError("truncated tokens buffer".to_string());
+        let mut outcomes = Vec::new();
+        let mut rest = tokens;
+        while !rest.is_empty() {
+            let (len, tail) = rest.split_first_chunk::<4>().ok_or_else(truncated)?;
+            let len = u32::from_le_bytes(*len) as usize;
+            if tail.len() < len {
+                return Err(truncated());
+            }
+            let (token, tail) = tail.split_at(len);
+            let outcome = authorize_one(builder, run_limits, token, root_public_key);
+            outcomes.extend(outcome.to_le_bytes());
+            rest = tail;
+        }
+        Ok(outcomes)
+    }
+);
+
 // drop a authorizer
 // Input:
 // authorizer: a pointer to an authorizer allocated in the wasm memory
@@ -1,6 +1,18 @@
-use crate::wasm_export;
+use crate::crypto::SignatureAlgorithm;
+use crate::crypto::public_key::parse_public_key;
+use crate::term_json::term_from_json;
 use crate::wasm_result::WasmResult;
-use biscuit_auth::{Authorizer, Biscuit, BiscuitBuilder, PublicKey};
+use crate::{make_rng, wasm_export};
+use biscuit_auth::builder::Term;
+use biscuit_auth::datalog::RunLimits;
+use biscuit_auth::error::{Format, Token};
+use biscuit_auth::{
+    Algorithm, Authorizer, AuthorizerBuilder, Biscuit, BiscuitBuilder, BlockBuilder, KeyPair,
+    PublicKey, ThirdPartyBlock, ThirdPartyRequest,
+};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::time::Duration;
 
 // create a new biscuit builder
 // Output:
@@ -138,3 +150,219 @@ wasm_export!(
         biscuit.to_vec()
     }
 );
+
+// get the root key id of a biscuit
+// Input:
+// biscuit: a pointer to a biscuit allocated in the wasm memory
+//
+// Output:
+// returnArea { data, data_len=0, kind }
+//
+// if kind = Ok
+// data is the root key id
+//
+// if kind = OkNone
+// data is 0 because the biscuit has no root key id
+//
+// kind is never an error because the function never fails
+wasm_export!(
+    fn biscuit_root_key_id(biscuit: &Biscuit) -> Option<u32> {
+        biscuit.root_key_id()
+    }
+);
+
+// get the context of a block
+// Input:
+// biscuit: a pointer to a biscuit allocated in the wasm memory
+// index: the index of the block, 0 being the authority block
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the context string allocated in the wasm memory
+// data_len is the length of the context
+//
+// if kind = OkNone
+// the block has no context, data is 0 and data_len is 0
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn biscuit_block_context(
+        biscuit: &Biscuit,
+        index: u32,
+    ) -> Result<Option<String>, biscuit_auth::error::Token> {
+        let index = index as usize;
+        match biscuit.context().into_iter().nth(index) {
+            Some(context) => Ok(context),
+            None => Err(Format::InvalidBlockId(index).into()),
+        }
+    }
+);
+
+// attenuate a biscuit by appending a block
+// Input:
+// biscuit: a pointer to a biscuit allocated in the wasm memory
+// builder: a pointer to the block builder allocated in the wasm memory, consumed by the call
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the new biscuit allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn biscuit_append(
+        biscuit: &Biscuit,
+        builder: Box<BlockBuilder>,
+    ) -> Result<Box<Biscuit>, biscuit_auth::error::Token> {
+        let next_keypair = KeyPair::new_with_rng(Algorithm::Ed25519, &mut make_rng());
+        Ok(Box::new(
+            biscuit.append_with_keypair(&next_keypair, *builder)?,
+        ))
+    }
+);
+
+// create the request to send to a third party to get a third party block
+// Input:
+// biscuit: a pointer to a biscuit allocated in the wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the third party request allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn biscuit_third_party_request(
+        biscuit: &Biscuit,
+    ) -> Result<Box<ThirdPartyRequest>, biscuit_auth::error::Token> {
+        Ok(Box::new(biscuit.third_party_request()?))
+    }
+);
+
+// append a block signed by a third party
+// Input:
+// biscuit: a pointer to the biscuit which created the request allocated in the wasm memory
+// external_key: a pointer to the public key of the third party allocated in the wasm memory
+// block: a pointer to the third party block allocated in the wasm memory, consumed by the call
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the new biscuit allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn biscuit_append_third_party(
+        biscuit: &Biscuit,
+        external_key: &PublicKey,
+        block: Box<ThirdPartyBlock>,
+    ) -> Result<Box<Biscuit>, biscuit_auth::error::Token> {
+        let next_keypair = KeyPair::new_with_rng(Algorithm::Ed25519, &mut make_rng());
+        Ok(Box::new(biscuit.append_third_party_with_keypair(
+            *external_key,
+            *block,
+            next_keypair,
+        )?))
+    }
+);
+
+/// Reads datalog parameters from a JSON object of terms, an empty string means no parameters
+fn parse_params(params_json: &str) -> Result<HashMap<String, Term>, Token> {
+    if params_json.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let params: HashMap<String, serde_json::Value> = serde_json::from_str(params_json)
+        .map_err(|e| Token::ConversionError(format!("invalid parameters: {e}")))?;
+    params
+        .into_iter()
+        .map(|(name, value)| match term_from_json(value) {
+            Ok(term) => Ok((name, term)),
+            Err(e) => Err(Token::ConversionError(format!(
+                "invalid parameter {name}: {e}"
+            ))),
+        })
+        .collect()
+}
+
+/// Run limits of `biscuit_verify_and_authorize`, missing fields keep the defaults of biscuit-auth
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RunLimitsJson {
+    max_facts: Option<u64>,
+    max_iterations: Option<u64>,
+    /// in milliseconds
+    max_time: Option<u64>,
+}
+
+/// Reads run limits from a JSON object, an empty string means the default limits
+fn parse_run_limits(limits_json: &str) -> Result<RunLimits, Token> {
+    let defaults = RunLimits::default();
+    if limits_json.is_empty() {
+        return Ok(defaults);
+    }
+    let limits: RunLimitsJson = serde_json::from_str(limits_json)
+        .map_err(|e| Token::ConversionError(format!("invalid run limits: {e}")))?;
+    Ok(RunLimits {
+        max_facts: limits.max_facts.unwrap_or(defaults.max_facts),
+        max_iterations: limits.max_iterations.unwrap_or(defaults.max_iterations),
+        max_time: limits
+            .max_time
+            .map_or(defaults.max_time, Duration::from_millis),
+    })
+}
+
+// verify a biscuit and authorize it in a single call, nothing has to be freed but the result
+// Input:
+// token {ptr, len}: the base64 encoded biscuit
+// public_key {ptr, len}: the root public key in hex format, prefixed with its algorithm
+// (`ed25519/<hex>` or `secp256r1/<hex>`), unprefixed keys are Ed25519 keys
+// authorizer_code {ptr, len}: the datalog code of the authorizer
+// params_json {ptr, len}: the values of the `{name}` parameters of the code, as a JSON object
+// of terms (e.g. {"operation": "read", "now": {"date": 1700000000}}), or an empty string
+// limits_json {ptr, len}: the run limits of the authorizer, as a JSON object
+// {"max_facts": 1000, "max_iterations": 100, "max_time": <milliseconds>}, missing fields and
+// an empty string keep the defaults of biscuit-auth (1000 facts, 100 iterations, 1 millisecond)
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the index of the matched allow policy
+// data_len is 0 because of a number type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn biscuit_verify_and_authorize(
+        token: &str,
+        public_key: &str,
+        authorizer_code: &str,
+        params_json: &str,
+        limits_json: &str,
+    ) -> Result<u32, Token> {
+        let public_key = parse_public_key(public_key, SignatureAlgorithm::default())?;
+        let biscuit = Biscuit::from_base64(token, public_key)?;
+        let mut authorizer = AuthorizerBuilder::new()
+            .code_with_params(authorizer_code, parse_params(params_json)?, HashMap::new())?
+            .set_limits(parse_run_limits(limits_json)?)
+            .build(&biscuit)?;
+        Ok(authorizer.authorize()? as u32)
+    }
+);
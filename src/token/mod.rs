@@ -1,2 +1,4 @@
-mod authorizer;
-mod biscuit;
+pub(crate) mod authorizer;
+pub(crate) mod biscuit;
+pub(crate) mod third_party;
+mod world;
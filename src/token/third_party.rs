@@ -0,0 +1,126 @@
+use crate::wasm_export;
+use crate::wasm_result::WasmResult;
+use biscuit_auth::{BlockBuilder, PrivateKey, ThirdPartyBlock, ThirdPartyRequest};
+
+// create a third party block request from the serialized request
+// Input:
+// data {ptr, len}: the base64 encoded request, as returned by third_party_request_to_base64
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the third party request allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn third_party_request_from_base64(
+        data: &str,
+    ) -> Result<Box<ThirdPartyRequest>, biscuit_auth::error::Token> {
+        Ok(Box::new(ThirdPartyRequest::deserialize_base64(data)?))
+    }
+);
+
+// convert a third party block request to base64, to send it to the third party
+// Input:
+// request: a pointer to a third party request allocated in the wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the base64 encoded string allocated in the wasm memory
+// data_len is the length of the base64 encoded string
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn third_party_request_to_base64(
+        request: &ThirdPartyRequest,
+    ) -> Result<String, biscuit_auth::error::Token> {
+        request.serialize_base64()
+    }
+);
+
+// create a third party block, signed with the private key of the third party
+// Input:
+// request: a pointer to a third party request allocated in the wasm memory, consumed by the call
+// private_key: a pointer to the private key of the third party allocated in the wasm memory
+// builder: a pointer to the block builder allocated in the wasm memory, consumed by the call
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is a pointer to the third party block allocated in the wasm memory
+// data_len is 0 because of the opaque type
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn third_party_request_create_block(
+        request: Box<ThirdPartyRequest>,
+        private_key: &PrivateKey,
+        builder: Box<BlockBuilder>,
+    ) -> Result<Box<ThirdPartyBlock>, biscuit_auth::error::Token> {
+        Ok(Box::new(request.create_block(private_key, *builder)?))
+    }
+);
+
+// drop a third party block request
+// Input:
+// request: a pointer to a third party request allocated in the wasm memory
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+//
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn third_party_request_drop(request: Box<ThirdPartyRequest>) {
+        drop(request);
+    }
+);
+
+// convert a third party block to base64
+// biscuit-auth cannot parse it back, the block must be appended with the module that created it
+// Input:
+// block: a pointer to a third party block allocated in the wasm memory
+//
+// Output:
+// returnArea { data, data_len, kind }
+//
+// if kind = Ok
+// data is the pointer to the base64 encoded string allocated in the wasm memory
+// data_len is the length of the base64 encoded string
+//
+// if kind = ErrBiscuit or kind = ErrSerialization
+// data is the pointer to the error message (JSON when ErrBiscuit, plain string when ErrSerialization)
+// data_len is the length of the error message
+wasm_export!(
+    fn third_party_block_to_base64(
+        block: &ThirdPartyBlock,
+    ) -> Result<String, biscuit_auth::error::Token> {
+        block.serialize_base64()
+    }
+);
+
+// drop a third party block
+// Input:
+// block: a pointer to a third party block allocated in the wasm memory
+// Output:
+// returnArea { data=0, data_len=0, kind=Ok }
+//
+// data is 0 because no data is returned
+// data_len is 0 because no data is returned
+// kind is Ok because the function never fails
+wasm_export!(
+    fn third_party_block_drop(block: Box<ThirdPartyBlock>) {
+        drop(block);
+    }
+);
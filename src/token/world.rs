@@ -0,0 +1,121 @@
+use crate::snapshot::SnapshotPrinter;
+use biscuit_auth::Authorizer;
+use biscuit_auth::error::Format;
+use biscuit_auth::format::schema;
+use serde::Serialize;
+
+/// Content of an authorizer, every element printed as datalog
+#[derive(Serialize)]
+pub(crate) struct AuthorizerWorldJson {
+    /// facts grouped by the set of blocks they come from
+    pub facts: Vec<FactsJson>,
+    pub rules: Vec<ElementJson>,
+    pub checks: Vec<ElementJson>,
+    pub policies: Vec<ElementJson>,
+}
+
+/// Facts sharing the same origins
+#[derive(Serialize)]
+pub(crate) struct FactsJson {
+    /// `authority`, `block N` or `authorizer`
+    pub origin: Vec<String>,
+    pub facts: Vec<String>,
+}
+
+/// Rule, check or policy with the block it comes from
+#[derive(Serialize)]
+pub(crate) struct ElementJson {
+    /// `authority`, `block N` or `authorizer`
+    pub origin: String,
+    pub code: String,
+}
+
+/// Position of the authorizer in the origins, after the blocks
+const AUTHORIZER_ORIGIN: usize = usize::MAX;
+
+fn origin_name(origin: usize) -> String {
+    match origin {
+        0 => "authority".to_string(),
+        AUTHORIZER_ORIGIN => "authorizer".to_string(),
+        index => format!("block {index}"),
+    }
+}
+
+fn with_origin(origin: usize, elements: Vec<String>) -> impl Iterator<Item = ElementJson> {
+    elements.into_iter().map(move |code| ElementJson {
+        origin: origin_name(origin),
+        code,
+    })
+}
+
+impl AuthorizerWorldJson {
+    pub(crate) fn from_authorizer(authorizer: &Authorizer) -> Result<Self, Format> {
+        let world = authorizer.snapshot()?.world;
+        let printer = SnapshotPrinter::new(&world)?;
+
+        let mut facts = world
+            .generated_facts
+            .iter()
+            .map(|generated| {
+                let mut origin = generated
+                    .origins
+                    .iter()
+                    .map(|origin| match origin.content {
+                        Some(schema::origin::Content::Authorizer(_)) => Ok(AUTHORIZER_ORIGIN),
+                        Some(schema::origin::Content::Origin(index)) => Ok(index as usize),
+                        None => Err(Format::DeserializationError(
+                            "deserialization error: expected `content` field in Origin".to_string(),
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                origin.sort_unstable();
+                let mut facts = printer.facts(&generated.facts)?;
+                facts.sort_unstable();
+                Ok((origin, facts))
+            })
+            .collect::<Result<Vec<_>, Format>>()?;
+        // the authorizer stores facts in hash maps, sort them to get a stable output
+        facts.sort_unstable();
+
+        let mut rules = Vec::new();
+        let mut checks = Vec::new();
+        for (index, block) in world.blocks.iter().enumerate() {
+            let version = block.version.unwrap_or(0);
+            rules.extend(with_origin(index, printer.rules(&block.rules, version)?));
+            checks.extend(with_origin(index, printer.checks(&block.checks, version)?));
+        }
+        let block = &world.authorizer_block;
+        let version = block.version.unwrap_or(0);
+        rules.extend(with_origin(
+            AUTHORIZER_ORIGIN,
+            printer.rules(&block.rules, version)?,
+        ));
+        checks.extend(with_origin(
+            AUTHORIZER_ORIGIN,
+            printer.checks(&block.checks, version)?,
+        ));
+        let policies = with_origin(
+            AUTHORIZER_ORIGIN,
+            printer.policies(&world.authorizer_policies, version)?,
+        )
+        .collect();
+
+        Ok(AuthorizerWorldJson {
+            facts: facts
+                .into_iter()
+                .map(|(origin, facts)| FactsJson {
+                    origin: origin.into_iter().map(origin_name).collect(),
+                    facts,
+                })
+                .collect(),
+            rules,
+            checks,
+            policies,
+        })
+    }
+
+    pub(crate) fn to_json(&self) -> Result<String, Format> {
+        serde_json::to_string(self)
+            .map_err(|e| Format::SerializationError(format!("serialization error: {e:?}")))
+    }
+}
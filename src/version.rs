@@ -0,0 +1,85 @@
+use crate::wasm_export;
+use crate::wasm_result::WasmResult;
+use serde_json::json;
+
+/// Version of the export signatures and of the return area layout, changed on any
+/// incompatible change of `abi/biscuit_wasm_shim.json`
+pub const ABI_VERSION: u32 = 1;
+
+/// Version of the biscuit-auth dependency, checked against Cargo.lock by the tests
+pub const BISCUIT_AUTH_VERSION: &str = "6.0.0";
+
+/// Cargo features of the shim, `shim_features` lists the enabled ones
+const FEATURES: &[(&str, bool)] = &[
+    ("print", cfg!(feature = "print")),
+    ("extern-funcs", cfg!(feature = "extern-funcs")),
+    ("dispatch", cfg!(feature = "dispatch")),
+    ("component", cfg!(feature = "component")),
+    ("manifest", cfg!(feature = "manifest")),
+    ("catch-panic", cfg!(feature = "catch-panic")),
+];
+
+// version of the shim crate
+// Output:
+// returnArea { data, data_len, kind=Ok }
+// data is the pointer to the version string allocated in the wasm memory
+// data_len is the length of the version string
+// kind is Ok because the function never fails
+wasm_export!(
+    fn shim_version() -> String {
+        env!("CARGO_PKG_VERSION").to_string()
+    }
+);
+
+// version of the biscuit-auth library the shim is built with
+// Output:
+// returnArea { data, data_len, kind=Ok }
+// data is the pointer to the version string allocated in the wasm memory
+// data_len is the length of the version string
+// kind is Ok because the function never fails
+wasm_export!(
+    fn biscuit_auth_version() -> String {
+        BISCUIT_AUTH_VERSION.to_string()
+    }
+);
+
+// version of the ABI (export signatures, ownership of the parameters, return area layout),
+// bindings should refuse a module with a different version
+// Output:
+// returnArea { data, data_len=0, kind=Ok }
+// data is the ABI version
+// data_len is 0 because of a number type
+// kind is Ok because the function never fails
+wasm_export!(
+    fn abi_version() -> u32 {
+        ABI_VERSION
+    }
+);
+
+// what the module supports, as JSON:
+// { "features": ["print", ..], "algorithms": ["ed25519", "secp256r1"],
+//   "datalog_versions": ["3.0", "3.1", "3.2", "3.3"], "schema_versions": { "min": 3, "max": 6 } }
+// features are the enabled cargo features, schema_versions the versions of the token
+// serialization format that can be read
+// Output:
+// returnArea { data, data_len, kind=Ok }
+// data is the pointer to the JSON string allocated in the wasm memory
+// data_len is the length of the JSON string
+// kind is Ok because the function never fails
+wasm_export!(
+    fn shim_features() -> String {
+        let features: Vec<_> = FEATURES
+            .iter()
+            .filter(|(_, enabled)| *enabled)
+            .map(|(name, _)| name)
+            .collect();
+        // MIN_SCHEMA_VERSION and MAX_SCHEMA_VERSION of biscuit-auth, which are not exported
+        json!({
+            "features": features,
+            "algorithms": ["ed25519", "secp256r1"],
+            "datalog_versions": ["3.0", "3.1", "3.2", "3.3"],
+            "schema_versions": { "min": 3, "max": 6 },
+        })
+        .to_string()
+    }
+);
@@ -6,11 +6,25 @@ macro_rules! wasm_export {
     (fn $f:ident($($arg_name:ident: $arg_type:ty),* $(,)?) -> $ret:ty $body:block) => {
         #[unsafe(no_mangle)]
         pub fn $f(__ret: &mut WasmResult, $($arg_name: $arg_type),*) {
+            $crate::panic::install_hook();
             #[allow(unused_mut)]
             let mut ret = (|| -> $ret {
                 $body
             });
-            __ret.capture(ret())
+            #[cfg(not(feature = "catch-panic"))]
+            __ret.capture(ret());
+            #[cfg(feature = "catch-panic")]
+            $crate::panic::catch(__ret, ret);
+        }
+
+        #[cfg(feature = "manifest")]
+        inventory::submit! {
+            $crate::manifest::Export {
+                name: stringify!($f),
+                module: module_path!(),
+                params: &[$($crate::manifest::Param::new::<$arg_type>(stringify!($arg_name))),*],
+                result: $crate::manifest::Output::of::<$ret>(),
+            }
         }
     };
 }
@@ -1,5 +1,6 @@
+use crate::secret::SecretString;
 use serde::Serialize;
-use std::mem;
+use zeroize::Zeroize;
 
 /// Struct to return bytes from a function
 /// which returns a Result<Box<T>, E>
@@ -24,6 +25,11 @@ pub enum ResultKind {
     Ok = 0,
     ErrBiscuit = 1,
     ErrSerialization = 2,
+    /// The call succeeded without a value (Option::None), data and data_len are 0
+    OkNone = 3,
+    /// The call panicked, data is the panic message (feature `catch-panic`)
+    #[cfg_attr(not(feature = "catch-panic"), allow(dead_code))]
+    ErrPanic = 4,
 }
 
 /// Trait to data into WasmResult
@@ -32,21 +38,23 @@ pub trait IntoWasmResult {
 }
 impl IntoWasmResult for String {
     fn into_wasm_result(self, ret: &mut WasmResult) {
-        ret.ptr = self.as_ptr();
-        ret.len = self.len();
+        ret.leak_bytes(self.into_bytes());
         ret.kind = ResultKind::Ok;
-        // leak the string dropped by the caller
-        mem::forget(self);
     }
 }
 
 impl IntoWasmResult for Vec<u8> {
     fn into_wasm_result(self, ret: &mut WasmResult) {
-        ret.ptr = self.as_ptr();
-        ret.len = self.len();
+        ret.leak_bytes(self);
+        ret.kind = ResultKind::Ok;
+    }
+}
+
+impl IntoWasmResult for SecretString {
+    fn into_wasm_result(self, ret: &mut WasmResult) {
+        // leak the string wiped and freed by the caller with secret_string_free
+        (ret.ptr, ret.len) = self.into_raw();
         ret.kind = ResultKind::Ok;
-        // leak the vec dropped by the caller
-        mem::forget(self);
     }
 }
 
@@ -78,6 +86,20 @@ impl IntoWasmResult for () {
     }
 }
 
+impl<T: IntoWasmResult> IntoWasmResult for Option<T> {
+    fn into_wasm_result(self, ret: &mut WasmResult) {
+        match self {
+            Some(value) => value.into_wasm_result(ret),
+            // a dedicated kind, since 0 can be a valid value for numbers
+            None => {
+                ret.ptr = std::ptr::null();
+                ret.len = 0;
+                ret.kind = ResultKind::OkNone;
+            }
+        }
+    }
+}
+
 impl<T: IntoWasmResult, E: Serialize> IntoWasmResult for Result<T, E> {
     fn into_wasm_result(self, ret: &mut WasmResult) {
         match self {
@@ -95,9 +117,7 @@ impl<T: IntoWasmResult, E: Serialize> IntoWasmResult for Result<T, E> {
                         serialization_error.to_string()
                     }
                 };
-                ret.ptr = msg.as_ptr();
-                ret.len = msg.len();
-                mem::forget(msg);
+                ret.leak_bytes(msg.into_bytes());
             }
         }
     }
@@ -120,4 +140,171 @@ impl WasmResult {
     pub fn capture<T: IntoWasmResult>(&mut self, value: T) {
         value.into_wasm_result(self)
     }
+
+    /// Leaks bytes returned to the host, shrunk to their length
+    ///
+    /// The return area has no room for a capacity: the allocation is exactly `len` bytes,
+    /// so that `free` can rebuild its layout from `ptr` and `len` alone.
+    fn leak_bytes(&mut self, bytes: Vec<u8>) {
+        let bytes = Box::into_raw(bytes.into_boxed_slice());
+        self.ptr = bytes as *const u8;
+        self.len = bytes.len();
+    }
+
+    /// Takes back the bytes leaked by `leak_bytes`, if any
+    fn take_bytes(&mut self) -> Option<Box<[u8]>> {
+        // opaque pointers, numbers and empty buffers have a length of 0
+        let bytes = (self.len != 0).then(|| unsafe {
+            Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                self.ptr as *mut u8,
+                self.len,
+            ))
+        });
+        self.ptr = std::ptr::null();
+        self.len = 0;
+        bytes
+    }
+
+    /// Frees the bytes owned by the result, if any.
+    ///
+    /// Strings, byte buffers and error messages are owned by the result and
+    /// allocated with exactly their length. Opaque `Box<T>` pointers are not
+    /// owned by the result and must be released with their dedicated `*_drop` function.
+    ///
+    /// The bytes are wiped before being freed, so secrets can be released
+    /// this way too. The result is reset afterward, freeing it twice is a no-op.
+    pub fn free(&mut self) {
+        if let Some(mut bytes) = self.take_bytes() {
+            bytes.zeroize();
+        }
+    }
+}
+
+#[cfg(feature = "catch-panic")]
+impl WasmResult {
+    /// Returns the message of a panic caught in an export
+    pub(crate) fn capture_panic(&mut self, message: String) {
+        self.leak_bytes(message.into_bytes());
+        self.kind = ResultKind::ErrPanic;
+    }
+}
+
+#[cfg(any(
+    test,
+    feature = "extern-funcs",
+    feature = "dispatch",
+    feature = "component"
+))]
+impl WasmResult {
+    /// Empty return area, as returned by `get_return_area`
+    pub(crate) fn new() -> Self {
+        unsafe { std::mem::zeroed() }
+    }
+}
+
+#[cfg(test)]
+impl WasmResult {
+    /// Reads a secret string returned by a call and releases it with `secret_string_free`,
+    /// as the host does
+    pub(crate) fn into_secret(self) -> Result<String, String> {
+        let ok = self.into_error()?;
+        let bytes = unsafe { std::slice::from_raw_parts(ok.ptr, ok.len) };
+        let secret = String::from_utf8(bytes.to_vec()).unwrap();
+        crate::secret::secret_string_free(ok.ptr as *mut u8, ok.len);
+        Ok(secret)
+    }
+}
+
+#[cfg(feature = "extern-funcs")]
+impl WasmResult {
+    /// Takes the bytes of a result filled by the host, allocated with `malloc(len, 1)`
+    ///
+    /// Returns the data when the kind is Ok, the error message otherwise.
+    pub(crate) fn into_host_bytes(self) -> Result<Vec<u8>, Vec<u8>> {
+        let bytes = if self.len == 0 {
+            Vec::new()
+        } else {
+            unsafe { Vec::from_raw_parts(self.ptr as *mut u8, self.len, self.len) }
+        };
+        match self.kind {
+            ResultKind::Ok => Ok(bytes),
+            _ => Err(bytes),
+        }
+    }
+}
+
+#[cfg(feature = "component")]
+impl WasmResult {
+    /// Whether the error message is JSON, as opposed to a plain serialization error
+    pub(crate) fn is_biscuit_error(&self) -> bool {
+        matches!(self.kind, ResultKind::ErrBiscuit)
+    }
+}
+
+#[cfg(any(test, feature = "dispatch", feature = "component"))]
+impl WasmResult {
+    /// Takes the error message out of the result, if any
+    fn into_error(self) -> Result<Self, String> {
+        match self.kind {
+            ResultKind::Ok | ResultKind::OkNone => Ok(self),
+            ResultKind::ErrBiscuit | ResultKind::ErrSerialization | ResultKind::ErrPanic => {
+                Err(String::from_utf8(self.into_owned_bytes()).unwrap())
+            }
+        }
+    }
+
+    fn into_owned_bytes(mut self) -> Vec<u8> {
+        self.take_bytes().map(Vec::from).unwrap_or_default()
+    }
+
+    /// Reads the opaque pointer returned by a call, or its error message
+    pub(crate) fn into_box<T>(self) -> Result<Box<T>, String> {
+        let ok = self.into_error()?;
+        Ok(unsafe { Box::from_raw(ok.ptr as *mut T) })
+    }
+
+    /// Reads the bytes returned by a call, or its error message
+    pub(crate) fn into_bytes(self) -> Result<Vec<u8>, String> {
+        Ok(self.into_error()?.into_owned_bytes())
+    }
+
+    /// Reads the string returned by a call, or its error message
+    pub(crate) fn into_string(self) -> Result<String, String> {
+        Ok(String::from_utf8(self.into_bytes()?).unwrap())
+    }
+
+    /// Reads the number returned by a call, or its error message
+    pub(crate) fn into_u32(self) -> Result<u32, String> {
+        Ok(self.into_error()?.ptr as usize as u32)
+    }
+
+    /// Reads the optional number returned by a call, or its error message
+    pub(crate) fn into_option_u32(self) -> Result<Option<u32>, String> {
+        let ok = self.into_error()?;
+        match ok.kind {
+            ResultKind::OkNone => Ok(None),
+            _ => Ok(Some(ok.ptr as usize as u32)),
+        }
+    }
+
+    /// Reads the optional string returned by a call, or its error message
+    pub(crate) fn into_option_string(self) -> Result<Option<String>, String> {
+        let ok = self.into_error()?;
+        match ok.kind {
+            ResultKind::OkNone => Ok(None),
+            _ => Ok(Some(String::from_utf8(ok.into_owned_bytes()).unwrap())),
+        }
+    }
+
+    /// Fills the result the way the host does, with a buffer of exactly the size of `bytes`
+    #[cfg(all(test, feature = "extern-funcs"))]
+    pub(crate) fn fill_as_host(&mut self, bytes: Vec<u8>, kind: ResultKind) {
+        self.leak_bytes(bytes);
+        self.kind = kind;
+    }
+
+    /// Checks that a call returning nothing succeeded
+    pub(crate) fn into_unit(self) -> Result<(), String> {
+        self.into_error().map(|_| ())
+    }
 }